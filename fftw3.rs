@@ -11,25 +11,81 @@ extern crate sync;
 
 use num::complex::Cmplx;
 
-use fftw3_bindgen::{FFTW_FORWARD, FFTW_BACKWARD, FFTW_ESTIMATE,
-                    fftw_alloc_complex, fftw_alloc_real, fftw_free,
-                    fftw_plan_dft_1d, fftw_plan_dft_r2c_1d, fftw_plan_dft_c2r_1d,
-                    fftw_destroy_plan, fftw_execute, fftw_plan};
+use fftw3_bindgen::{FFTW_FORWARD, FFTW_BACKWARD,
+                    fftw_alloc_real, fftw_free, fftwf_alloc_real, fftwf_free,
+                    fftw_destroy_plan, fftw_execute, fftw_execute_dft, fftw_execute_dft_r2c,
+                    fftw_execute_dft_c2r, fftw_execute_r2r, fftw_alignment_of, fftw_plan,
+                    fftwf_destroy_plan, fftwf_execute, fftwf_execute_dft,
+                    fftwf_execute_dft_r2c, fftwf_execute_dft_c2r, fftwf_execute_r2r};
+#[cfg(target_arch = "x86_64")]
+use fftw3_bindgen::{c_long_double, fftwl_complex, fftwl_destroy_plan, fftwl_execute,
+                    fftwl_execute_dft, fftwl_execute_dft_r2c, fftwl_execute_dft_c2r,
+                    fftwl_execute_r2r};
+
+use fftw3_cache::describe;
+use fftw3_memo::plan_memoized;
 
 use std::cast::transmute;
 use std::fmt::{Show, Formatter};
 use std::fmt;
 use std::libc::{c_int, c_void, size_t};
 use std::mem::move_val_init;
-use std::ptr::copy_memory;
+use std::ptr::{copy_memory, mut_null};
+use std::vec::Chunks;
 
+use sync::Arc;
 use sync::mutex::{StaticMutex, MUTEX_INIT};
 pub use iteration::HermitianItems;
+pub use fftw3_r2r::{R2rKind, Dct1, Dct2, Dct3, Dct4, Dst1, Dst2, Dst3, Dst4,
+                    R2hc, Hc2r, Dht};
+pub use fftw3_r2r::{halfcomplex_to_cmplx, cmplx_to_halfcomplex};
+pub use fftw3_guru::{GuruLayout, IoDim};
+pub use fftw3_split::SplitFftw;
+pub use fftw3_inplace::InPlaceFftw;
+pub use fftw3_float::FftwFloat;
+pub use fftw3_planner::{Rigor, Estimate, Measure, Patient, Exhaustive, Planner,
+                        ToPlanner};
+#[cfg(fftw3_threads)]
+pub use fftw3_planner::init_threads;
+pub use fftw3_wisdom::{Wisdom, WisdomReport, init_wisdom};
+pub use fftw3_cache::PlanCache;
+pub use fftw3_shared::SharedPlan;
+pub use fftw3_pool::FftwPool;
+pub use fftw3_memo::{MemoStats, MemoGuard, enable_memo, memo_capacity, memo_stats,
+                     clear_memo};
+#[cfg(target_arch = "x86_64")]
+pub use fftw3_long::{LongDouble, cmplx_to_long, long_to_cmplx};
+#[cfg(fftw3q)]
+pub use fftw3_quad::{Quad, cmplx_to_quad, quad_to_cmplx};
 
 mod fftw3_bindgen;
+mod fftw3_batch;
+mod fftw3_cache;
+// Only the wider types convert their values to and from f64.
+#[cfg(target_arch = "x86_64")]
+mod fftw3_convert;
+#[cfg(not(target_arch = "x86_64"), fftw3q)]
+mod fftw3_convert;
+mod fftw3_float;
+mod fftw3_guru;
+mod fftw3_inplace;
+#[cfg(target_arch = "x86_64")]
+mod fftw3_long;
+mod fftw3_memo;
+mod fftw3_planner;
+mod fftw3_pool;
+#[cfg(fftw3q)]
+mod fftw3_quad;
+mod fftw3_r2r;
+mod fftw3_shared;
+mod fftw3_split;
 mod fftw3_test;
+mod fftw3_wisdom;
 pub mod fftw3_macros;
 
+/// Serializes the calls to the planners of fftw3 : making and destroying plans, and
+/// using the wisdom. Computing a plan, on its arrays or on new ones, and allocating
+/// arrays are thread-safe and never take it.
 static mut LOCK: StaticMutex = MUTEX_INIT;
 
 /** Pretty-print an array of complex :
@@ -41,12 +97,12 @@ static mut LOCK: StaticMutex = MUTEX_INIT;
   ```
 **/
 
-pub enum CxDisplay<'a> {
-  Line(&'a[Cmplx<f64>]),
-  Col(&'a[Cmplx<f64>]),
+pub enum CxDisplay<'a, F> {
+  Line(&'a[Cmplx<F>]),
+  Col(&'a[Cmplx<F>]),
 }
 
-impl<'a> Show for CxDisplay<'a> {
+impl<'a, F: fmt::Float> Show for CxDisplay<'a, F> {
   fn fmt(&self, f: &mut Formatter) -> fmt::Result {
     let (sep, array) = match self {
       &Line(ref array) => (' ', array),
@@ -65,6 +121,83 @@ impl<'a> Show for CxDisplay<'a> {
   }
 }
 
+/// Errors reported when a transform cannot be prepared, or when the wisdom of the
+/// planner or its cache cannot be imported or exported.
+#[deriving(Eq)]
+pub enum FftwError {
+  /// The transform has no dimensions.
+  ZeroRank,
+  /// The dimension along the given axis is zero.
+  ZeroDimension(uint),
+  /// The number of values given does not match the size of the transform.
+  /// Holds the expected and the actual number of values.
+  SizeMismatch(uint, uint),
+  /// The strides and distances given make the signals of a batch overlap.
+  InvalidLayout,
+  /// The batch holds no signals.
+  EmptyBatch,
+  /// The kind of transform along the given axis does not support its size.
+  UnsupportedSize(uint),
+  /// fftw3 has no plan satisfying the options of the planner, as when preserving the
+  /// input of a multi-dimensional complex to real transform.
+  NoPlan,
+  /// The layout of the transform reaches values outside of its buffers.
+  OutOfBounds,
+  /// The transform does not compute this kind of transform.
+  KindMismatch,
+  /// The arrays do not have the alignment of the ones the transform was planned for, or
+  /// the one fftw3 requires for their values.
+  Misaligned,
+  /// The transform was planned for arrays of a different placement (in-place or
+  /// out-of-place).
+  InPlaceMismatch,
+  /// The string given does not hold wisdom of the expected precision.
+  InvalidWisdom,
+  /// The wisdom file at the given path could not be read or written, or does not hold
+  /// wisdom of the expected precision.
+  WisdomFile(Path),
+  /// The system does not hold wisdom of the expected precision.
+  NoSystemWisdom,
+  /// The plan cache directory at the given path could not be created or written.
+  CacheDir(Path),
+}
+
+impl Show for FftwError {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match *self {
+      ZeroRank => write!(f.buf, "the transform has no dimensions"),
+      ZeroDimension(axis) => write!(f.buf, "dimension {} of the transform is zero", axis),
+      SizeMismatch(expected, actual) =>
+        write!(f.buf, "expected {} values, got {}", expected, actual),
+      InvalidLayout => write!(f.buf, "the signals of the batch overlap"),
+      EmptyBatch => write!(f.buf, "the batch holds no signals"),
+      UnsupportedSize(axis) =>
+        write!(f.buf, "the transform does not support the size of dimension {}", axis),
+      NoPlan => write!(f.buf, "fftw3 could not plan the transform with these options"),
+      OutOfBounds => write!(f.buf, "the layout reaches values outside of the buffers"),
+      KindMismatch => write!(f.buf, "the transform does not compute this kind of transform"),
+      Misaligned => write!(f.buf, "the arrays are not aligned as the transform requires"),
+      InPlaceMismatch => write!(f.buf, "the arrays are not placed like the planned ones"),
+      InvalidWisdom => write!(f.buf, "the string does not hold valid wisdom"),
+      WisdomFile(ref path) =>
+        write!(f.buf, "could not use the wisdom file {}", path.display()),
+      NoSystemWisdom => write!(f.buf, "the system does not hold wisdom of this precision"),
+      CacheDir(ref path) =>
+        write!(f.buf, "could not use the plan cache directory {}", path.display()),
+    }
+  }
+}
+
+/// Kind of the transforms whose plan is applied to arrays given at each computation,
+/// the split and in-place ones.
+#[deriving(Eq)]
+enum TransformKind {
+  Forward,
+  Backward,
+  RealToComplex,
+  ComplexToReal,
+}
+
 priv struct CplxSlice<T> {
   data: *T,
   len: uint,
@@ -79,7 +212,7 @@ priv struct CplxSlice<T> {
 
     let input = [1f64, 0f64, 2f64, 4f64, 5f64, 2f64, 0f64, -1f64, -3f64];
     let mut fftw = Fftw::from_slice(input);
-    fftw.fft();
+    fftw.compute();
     println!("{}", Line(fftw.output()));
     ```
 
@@ -91,14 +224,34 @@ priv struct CplxSlice<T> {
 
     let input = [Cmplx::new(1f64, -1f64), Cmplx::new(0f64, 2f64), Cmplx::new(4f64, 12f64)];
     let mut fftw = Fftw::from_slice(input);
-    fftw.fft();
+    fftw.compute();
     println!("{}", Line(fftw.output()));
     ```
+
+    Compute the 2d transform of a 2x3 complex array, stored in row-major order :
+
+    ```rust
+    use num::complex::Cmplx;
+    use fftw3_rust::{Fftw, Line};
+
+    let mut fftw = Fftw::new_2d(2, 3).unwrap();
+    fftw.ref_input().push_slice(ca!{1+0, 2-1, 0+3, 4+4, 5-2, 1+1});
+    fftw.compute();
+    for row in fftw.output_rows() {
+      println!("{}", Line(row));
+    }
+    ```
 **/
 pub struct Fftw<In, Out> {
   priv in_data: In,
   priv out_data: Out,
-  priv plan: fftw_plan,
+  priv plan: Plan,
+  priv shape: ~[uint],
+  // Position of the arrays the plan was made for, from the start of each buffer.
+  priv offsets: (uint, uint),
+  // Length of the rows of the input and output buffers of a batch, which do not follow
+  // from the shape of its transforms.
+  priv rows: Option<(uint, uint)>,
 }
 
 pub struct FftBuf<T> {
@@ -107,80 +260,399 @@ pub struct FftBuf<T> {
   priv capacity: uint,
 }
 
+/// Precision of the values of a plan, selecting the fftw3 library computing it.
+#[deriving(Eq, Clone)]
+enum Precision {
+  Double,
+  Single,
+  #[cfg(target_arch = "x86_64")]
+  Extended,
+  #[cfg(fftw3q)]
+  Quadruple,
+}
+
+/// Every precision this library was built with.
+#[cfg(target_arch = "x86_64", not(fftw3q))]
+static PRECISIONS: &'static [Precision] = &[Double, Single, Extended];
+#[cfg(target_arch = "x86_64", fftw3q)]
+static PRECISIONS: &'static [Precision] = &[Double, Single, Extended, Quadruple];
+#[cfg(not(target_arch = "x86_64"), not(fftw3q))]
+static PRECISIONS: &'static [Precision] = &[Double, Single];
+#[cfg(not(target_arch = "x86_64"), fftw3q)]
+static PRECISIONS: &'static [Precision] = &[Double, Single, Quadruple];
+
+impl Show for Precision {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match *self {
+      Double => write!(f.buf, "double"),
+      Single => write!(f.buf, "single"),
+      #[cfg(target_arch = "x86_64")]
+      Extended => write!(f.buf, "extended"),
+      #[cfg(fftw3q)]
+      Quadruple => write!(f.buf, "quadruple"),
+    }
+  }
+}
+
+/// A plan created by fftw3, destroyed with the library of its precision.
+struct Plan {
+  raw: fftw_plan,
+  precision: Precision,
+  // The plan of the memo this one shares, which destroys it with its last user.
+  shared: Option<Arc<Plan>>,
+}
+
+impl Plan {
+  /// Returns None if fftw3 returned a null plan, when no plan satisfies the flags of
+  /// the planner.
+  #[inline]
+  fn new(raw: fftw_plan, precision: Precision) -> Option<Plan> {
+    if raw.is_null() {
+      None
+    } else {
+      Some(Plan {
+        raw: raw,
+        precision: precision,
+        shared: None,
+      })
+    }
+  }
+
+  /// Returns a plan computing nothing, for the transforms of zero values fftw3 does not
+  /// plan.
+  fn empty(precision: Precision) -> Plan {
+    Plan {
+      raw: mut_null(),
+      precision: precision,
+      shared: None,
+    }
+  }
+
+  /// Returns a plan sharing 'plan', which is destroyed once no plan shares it anymore.
+  fn share(plan: &Arc<Plan>) -> Plan {
+    Plan {
+      raw: plan.get().raw,
+      precision: plan.get().precision,
+      shared: Some(plan.clone()),
+    }
+  }
+
+  /// Computes the transform on the arrays the plan was made for.
+  #[inline]
+  fn execute(&self) {
+    if self.raw.is_null() {
+      return;
+    }
+    unsafe {
+      match self.precision {
+        Double => fftw_execute(self.raw),
+        Single => fftwf_execute(self.raw),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_execute(self.raw),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::execute(self.raw),
+      }
+    }
+  }
+
+  /// Computes the transform on the arrays of a transform, from the given offsets. A plan
+  /// shared with the memo was made for other arrays, so it goes through `execute_with`.
+  unsafe fn execute_on<Tin: TransformData, Tout: TransformData>(&self, input: &mut [Tin],
+                                                                 output: &mut [Tout],
+                                                                 offsets: (uint, uint)) {
+    match self.shared {
+      Some(_) => self.execute_with(input, output, offsets),
+      None => self.execute(),
+    }
+  }
+
+  /// Computes the transform on other arrays than the ones the plan was made for, from the
+  /// given offsets. fftw3 allows it from several threads at once. The arrays must match
+  /// the planned ones, see `PlannedArrays::check`.
+  unsafe fn execute_with<Tin: TransformData, Tout: TransformData>(&self, input: &mut [Tin],
+                                                                   output: &mut [Tout],
+                                                                   offsets: (uint, uint)) {
+    let (in_offset, out_offset) = offsets;
+    let i = input.as_mut_ptr().offset(in_offset as int);
+    let o = output.as_mut_ptr().offset(out_offset as int);
+    self.execute_raw(i as *mut c_void, TransformData::is_complex(input),
+                     o as *mut c_void, TransformData::is_complex(output));
+  }
+
+  /// Computes the transform on the arrays at 'i' and 'o', holding complex values when
+  /// 'in_cmplx' and 'out_cmplx' are set and real values otherwise.
+  unsafe fn execute_raw(&self, i: *mut c_void, in_cmplx: bool, o: *mut c_void,
+                        out_cmplx: bool) {
+    let raw = self.raw;
+    if raw.is_null() {
+      return;
+    }
+    match (self.precision, in_cmplx, out_cmplx) {
+      (Double, true, true) =>
+        fftw_execute_dft(raw, i as *mut Cmplx<f64>, o as *mut Cmplx<f64>),
+      (Double, false, true) =>
+        fftw_execute_dft_r2c(raw, i as *mut f64, o as *mut Cmplx<f64>),
+      (Double, true, false) =>
+        fftw_execute_dft_c2r(raw, i as *mut Cmplx<f64>, o as *mut f64),
+      (Double, false, false) =>
+        fftw_execute_r2r(raw, i as *mut f64, o as *mut f64),
+      (Single, true, true) =>
+        fftwf_execute_dft(raw, i as *mut Cmplx<f32>, o as *mut Cmplx<f32>),
+      (Single, false, true) =>
+        fftwf_execute_dft_r2c(raw, i as *mut f32, o as *mut Cmplx<f32>),
+      (Single, true, false) =>
+        fftwf_execute_dft_c2r(raw, i as *mut Cmplx<f32>, o as *mut f32),
+      (Single, false, false) =>
+        fftwf_execute_r2r(raw, i as *mut f32, o as *mut f32),
+      #[cfg(target_arch = "x86_64")]
+      (Extended, true, true) =>
+        fftwl_execute_dft(raw, i as *mut fftwl_complex, o as *mut fftwl_complex),
+      #[cfg(target_arch = "x86_64")]
+      (Extended, false, true) =>
+        fftwl_execute_dft_r2c(raw, i as *mut c_long_double, o as *mut fftwl_complex),
+      #[cfg(target_arch = "x86_64")]
+      (Extended, true, false) =>
+        fftwl_execute_dft_c2r(raw, i as *mut fftwl_complex, o as *mut c_long_double),
+      #[cfg(target_arch = "x86_64")]
+      (Extended, false, false) =>
+        fftwl_execute_r2r(raw, i as *mut c_long_double, o as *mut c_long_double),
+      #[cfg(fftw3q)]
+      (Quadruple, in_cmplx, out_cmplx) =>
+        fftw3_quad::execute_with(raw, i, o, in_cmplx, out_cmplx),
+    }
+  }
+}
+
+/// The arrays a plan was made for. The arrays it computes must have the same lengths and
+/// placement, and the same alignment at the offsets of the plan.
+struct PlannedArrays {
+  lens: (uint, uint),
+  offsets: (uint, uint),
+  alignments: (c_int, c_int),
+  in_place: bool,
+}
+
+impl PlannedArrays {
+  unsafe fn new<Tin, Tout>(input: *mut Tin, in_len: uint, output: *mut Tout, out_len: uint,
+                           offsets: (uint, uint)) -> PlannedArrays {
+    let (in_offset, out_offset) = offsets;
+    PlannedArrays {
+      lens: (in_len, out_len),
+      offsets: offsets,
+      alignments: (fftw_alignment_of(input.offset(in_offset as int) as *mut f64),
+                   fftw_alignment_of(output.offset(out_offset as int) as *mut f64)),
+      in_place: input as uint == output as uint,
+    }
+  }
+
+  /// Checks that a plan made for these arrays can compute 'input' into 'output'.
+  fn check<Tin, Tout>(&self, input: &mut [Tin],
+                      output: &mut [Tout]) -> Result<(), FftwError> {
+    let ((in_len, out_len), (in_offset, out_offset)) = (self.lens, self.offsets);
+    if input.len() != in_len {
+      return Err(SizeMismatch(in_len, input.len()));
+    } else if output.len() != out_len {
+      return Err(SizeMismatch(out_len, output.len()));
+    } else if self.in_place {
+      // Two distinct mutable slices never overlap.
+      return Err(InPlaceMismatch);
+    }
+    let alignments = unsafe {
+      (fftw_alignment_of(input.as_mut_ptr().offset(in_offset as int) as *mut f64),
+       fftw_alignment_of(output.as_mut_ptr().offset(out_offset as int) as *mut f64))
+    };
+    if alignments != self.alignments {
+      Err(Misaligned)
+    } else {
+      Ok(())
+    }
+  }
+}
+
+/// Returns `Misaligned` if 'data' does not have the alignment fftw3 requires for its
+/// values : the __float128 values of the quadruple precision need 16 bytes, which a
+/// vector of `Quad` does not guarantee, unlike the arrays allocated by fftw3.
+fn check_alignment<T: TransformData>(data: &[T]) -> Result<(), FftwError> {
+  match TransformData::precision(data) {
+    #[cfg(fftw3q)]
+    Quadruple if data.as_ptr() as uint % 16 != 0 => Err(Misaligned),
+    _ => Ok(()),
+  }
+}
+
+impl Drop for Plan {
+  fn drop(&mut self) {
+    if self.shared.is_some() || self.raw.is_null() {
+      return;
+    }
+    unsafe {
+      let _g = LOCK.lock();
+      match self.precision {
+        Double => fftw_destroy_plan(self.raw),
+        Single => fftwf_destroy_plan(self.raw),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_destroy_plan(self.raw),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::destroy_plan(self.raw),
+      }
+    }
+  }
+}
+
 trait TransformData: Pod {
   fn fftw_alloc(capacity: uint) -> *mut Self;
+  fn fftw_free(data: *mut Self);
   fn transform_size(input_capacity: uint, _: &[Self]) -> uint;
-  fn plan(N: uint, input: *mut Self, output: *mut Cmplx<f64>)-> fftw_plan;
-  fn plan_inv(N: uint, input: *mut Cmplx<f64>, output: *mut Self)-> fftw_plan;
+  fn is_complex(_: &[Self]) -> bool;
+  fn precision(_: &[Self]) -> Precision;
+}
+
+/// One dimensional transforms between values of type Self and complex values of type C.
+trait Transform<C>: TransformData {
+  fn plan(N: uint, input: *mut Self, output: *mut C,
+          planner: &Planner) -> Result<Plan, FftwError>;
+  fn plan_inv(N: uint, input: *mut C, output: *mut Self,
+              planner: &Planner) -> Result<Plan, FftwError>;
 }
 
 impl TransformData for f64 {
   #[inline]
   fn fftw_alloc(capacity: uint) -> *mut f64 {
     unsafe {
-      let _g = LOCK.lock();
       fftw_alloc_real(capacity as size_t)
     }
   }
 
+  #[inline]
+  fn fftw_free(data: *mut f64) {
+    unsafe {
+      fftw_free(data as *mut c_void);
+    }
+  }
+
   #[inline]
   fn transform_size(input_capacity: uint, _: &[f64]) -> uint {
     input_capacity/2 + 1
   }
 
   #[inline]
-  fn plan(N: uint, input: *mut f64, output: *mut Cmplx<f64>)-> fftw_plan {
+  fn is_complex(_: &[f64]) -> bool {
+    false
+  }
+
+  #[inline]
+  fn precision(_: &[f64]) -> Precision {
+    Double
+  }
+}
+
+impl Transform<Cmplx<f64>> for f64 {
+  #[inline]
+  fn plan(N: uint, input: *mut f64, output: *mut Cmplx<f64>,
+          planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft_r2c([N], input, output, planner)
+  }
+
+  #[inline]
+  fn plan_inv(N: uint, input: *mut Cmplx<f64>, output: *mut f64,
+              planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft_c2r([N], input, output, planner)
+  }
+}
+
+impl TransformData for f32 {
+  #[inline]
+  fn fftw_alloc(capacity: uint) -> *mut f32 {
     unsafe {
-      let _g = LOCK.lock();
-      fftw_plan_dft_r2c_1d(N as c_int, input, output, FFTW_ESTIMATE)
+      fftwf_alloc_real(capacity as size_t)
     }
   }
 
   #[inline]
-  fn plan_inv(N: uint, input: *mut Cmplx<f64>, output: *mut f64)-> fftw_plan {
+  fn fftw_free(data: *mut f32) {
     unsafe {
-      let _g = LOCK.lock();
-      fftw_plan_dft_c2r_1d(N as c_int, input, output, FFTW_ESTIMATE)
+      fftwf_free(data as *mut c_void);
     }
   }
+
+  #[inline]
+  fn transform_size(input_capacity: uint, _: &[f32]) -> uint {
+    input_capacity/2 + 1
+  }
+
+  #[inline]
+  fn is_complex(_: &[f32]) -> bool {
+    false
+  }
+
+  #[inline]
+  fn precision(_: &[f32]) -> Precision {
+    Single
+  }
+}
+
+impl Transform<Cmplx<f32>> for f32 {
+  #[inline]
+  fn plan(N: uint, input: *mut f32, output: *mut Cmplx<f32>,
+          planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft_r2c([N], input, output, planner)
+  }
+
+  #[inline]
+  fn plan_inv(N: uint, input: *mut Cmplx<f32>, output: *mut f32,
+              planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft_c2r([N], input, output, planner)
+  }
 }
 
-impl TransformData for Cmplx<f64> {
+impl<F: FftwFloat> TransformData for Cmplx<F> {
   #[inline]
-  fn fftw_alloc(capacity: uint) -> *mut Cmplx<f64> {
+  fn fftw_alloc(capacity: uint) -> *mut Cmplx<F> {
     unsafe {
-      let _g = LOCK.lock();
-      fftw_alloc_complex(capacity as size_t)
+      FftwFloat::alloc_complex(capacity)
     }
   }
 
   #[inline]
-  fn transform_size(input_capacity: uint, _: &[Cmplx<f64>]) -> uint {
+  fn fftw_free(data: *mut Cmplx<F>) {
+    // Every precision frees its complex arrays like its real ones.
+    TransformData::fftw_free(data as *mut F);
+  }
+
+  #[inline]
+  fn transform_size(input_capacity: uint, _: &[Cmplx<F>]) -> uint {
     input_capacity
   }
 
   #[inline]
-  fn plan(N: uint, input: *mut Cmplx<f64>, output: *mut Cmplx<f64>)-> fftw_plan {
-    unsafe {
-      let _g = LOCK.lock();
-      fftw_plan_dft_1d(N as c_int, input, output, FFTW_FORWARD, FFTW_ESTIMATE)
-    }
+  fn is_complex(_: &[Cmplx<F>]) -> bool {
+    true
   }
 
   #[inline]
-  fn plan_inv(N: uint, input: *mut Cmplx<f64>, output: *mut Cmplx<f64>)-> fftw_plan {
-    unsafe {
-      let _g = LOCK.lock();
-      fftw_plan_dft_1d(N as c_int, input, output, FFTW_BACKWARD, FFTW_ESTIMATE)
-    }
+  fn precision(_: &[Cmplx<F>]) -> Precision {
+    let real: &[F] = &[];
+    TransformData::precision(real)
+  }
+}
+
+impl<F: FftwFloat> Transform<Cmplx<F>> for Cmplx<F> {
+  #[inline]
+  fn plan(N: uint, input: *mut Cmplx<F>, output: *mut Cmplx<F>,
+          planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft([N], input, output, FFTW_FORWARD, planner)
+  }
+
+  #[inline]
+  fn plan_inv(N: uint, input: *mut Cmplx<F>, output: *mut Cmplx<F>,
+              planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft([N], input, output, FFTW_BACKWARD, planner)
   }
 }
 
 trait TransformBuf<T>: Vector<T>+Container {
   fn new(capacity: uint) -> Self;
   fn get_transformed_capacity(&self) -> uint;
-  fn make_plan(&mut self, out_data: &mut [Cmplx<f64>]) -> fftw_plan;
-  fn make_plan_inv(&mut self, in_data: &mut [Cmplx<f64>]) -> fftw_plan;
   fn ready(&self) -> bool;
   fn as_mut_slice<'a>(&'a mut self) -> &'a mut [T];
   fn mark_filled(&mut self);
@@ -197,16 +669,6 @@ impl<T: TransformData> TransformBuf<T> for ~[T] {
     TransformData::transform_size(self.len(), self.as_slice())
   }
 
-  #[inline]
-  fn make_plan(&mut self, out_data: &mut [Cmplx<f64>]) -> fftw_plan {
-    TransformData::plan(self.len(), self.as_mut_ptr(), out_data.as_mut_ptr())
-  }
-
-  #[inline]
-  fn make_plan_inv(&mut self, in_data: &mut [Cmplx<f64>]) -> fftw_plan {
-    TransformData::plan_inv(self.len(), in_data.as_mut_ptr(), self.as_mut_ptr())
-  }
-
   #[inline]
   fn ready(&self) -> bool {
     self.len() > 0
@@ -241,16 +703,6 @@ impl<T: TransformData> TransformBuf<T> for FftBuf<T> {
     TransformData::transform_size(self.capacity, self.as_slice())
   }
 
-  #[inline]
-  fn make_plan(&mut self, out_data: &mut [Cmplx<f64>]) -> fftw_plan {
-    TransformData::plan(self.capacity, self.data, out_data.as_mut_ptr())
-  }
-
-  #[inline]
-  fn make_plan_inv(&mut self, in_data: &mut [Cmplx<f64>]) -> fftw_plan {
-    TransformData::plan_inv(self.capacity, in_data.as_mut_ptr(), self.data)
-  }
-
   #[inline]
   fn ready(&self) -> bool {
     self.capacity == self.size && self.size > 0
@@ -292,6 +744,11 @@ impl<T: TransformData> FftBuf<T> {
     }
   }
 
+  /// Removes all the values of the buffer, so it can be filled again.
+  pub fn clear(&mut self) {
+    self.size = 0;
+  }
+
   /// Adds the elements of the given slice to the fft buffer.
   /// The capacity of the transform is fixed, if there are two many values
   /// in the slice no element will be added.
@@ -337,96 +794,497 @@ impl<T: TransformData> Container for FftBuf<T> {
 #[unsafe_destructor]
 impl<T: TransformData> Drop for FftBuf<T> {
   fn drop(&mut self) {
-    unsafe {
-      let _g = LOCK.lock();
-      fftw_free(self.data as *mut c_void);
-    }
+    TransformData::fftw_free(self.data);
   }
 }
 
-impl<T: TransformData> Fftw<~[T], ~[Cmplx<f64>]> {
+impl<C: TransformData, T: Transform<C>> Fftw<~[T], ~[C]> {
   /// Prepare a new transform using the given buffer. Does not perform any copy of
-  /// the input data. Using a regular vector might prevent the use of simd because
-  /// of alignment constraints.
-  pub fn from_vec(mut vec: ~[T]) -> Fftw<~[T], ~[Cmplx<f64>]> {
-    let mut _out: ~[Cmplx<f64>] = TransformBuf::new(vec.get_transformed_capacity());
-    let _p = vec.make_plan(_out);
-    Fftw {
+  /// the input data, unless planning with more rigor than `Estimate` : the values are
+  /// then saved while the planner overwrites the buffer. Using a regular vector might
+  /// prevent the use of simd because of alignment constraints, and fails for quadruple
+  /// precision values without the alignment of __float128, see `from_vec_with`.
+  pub fn from_vec(vec: ~[T]) -> Fftw<~[T], ~[C]> {
+    estimated(Fftw::from_vec_with(vec, Estimate))
+  }
+
+  /// Same as `from_vec`, planned with the options of 'planner'.
+  /// Returns `NoPlan` if fftw3 has no plan satisfying these options, and `Misaligned` if
+  /// the vector, or the output one, holds quadruple precision values without the 16
+  /// bytes alignment of __float128 : `FftBuf` is always properly aligned.
+  pub fn from_vec_with<P: ToPlanner>(mut vec: ~[T], planner: P)
+                                     -> Result<Fftw<~[T], ~[C]>, FftwError> {
+    let mut _out: ~[C] = TransformBuf::new(vec.get_transformed_capacity());
+    if_ok!(check_alignment(vec.as_slice()));
+    if_ok!(check_alignment(_out.as_slice()));
+    let planner = planner.to_planner();
+    let saved: Option<~[T]> = match planner.rigor() {
+      Estimate => None,
+      _ => Some(vec.iter().map(|&x| x).collect()),
+    };
+    let _p = if_ok!(Transform::plan(vec.len(), vec.as_mut_ptr(), _out.as_mut_ptr(),
+                                    &planner));
+    match saved {
+      Some(values) => unsafe {
+        copy_memory(vec.as_mut_ptr(), values.as_ptr(), values.len());
+      },
+      None => (),
+    }
+    let _shape = ~[vec.len()];
+    Ok(Fftw {
       in_data: vec,
       out_data: _out,
       plan: _p,
-    }
+      shape: _shape,
+      offsets: (0, 0),
+      rows: None,
+    })
   }
 }
 
-impl<T: TransformData> Fftw<FftBuf<T>, FftBuf<Cmplx<f64>>> {
+impl<C: TransformData, T: Transform<C>> Fftw<FftBuf<T>, FftBuf<C>> {
   /// Prepare a new transform from the given slice of numbers.
-  /// The elements of the slice are copied in an internal buffer allocated by fftw3.
+  /// The elements of the slice are copied in an internal buffer allocated by fftw3, once
+  /// the transform is planned.
   #[inline]
-  pub fn from_slice(slice: &[T]) -> Fftw<FftBuf<T>, FftBuf<Cmplx<f64>>> {
-    let mut new: Fftw<FftBuf<T>, FftBuf<Cmplx<f64>>> = Fftw::new(slice.len());
-    new.in_data.push_slice(slice);
-    new
+  pub fn from_slice(slice: &[T]) -> Fftw<FftBuf<T>, FftBuf<C>> {
+    estimated(Fftw::from_slice_with(slice, Estimate))
   }
 
-  /// Returns a mutable ref to the input buffer. You can use this to
-  /// add/remove elements from the input.
+  /// Same as `from_slice`, planned with the options of 'planner'.
+  /// Returns `NoPlan` if fftw3 has no plan satisfying these options.
   #[inline]
-  pub fn ref_input<'a>(&'a mut self) -> &'a mut FftBuf<T> {
-    &mut self.in_data
+  pub fn from_slice_with<P: ToPlanner>(slice: &[T], planner: P)
+                                       -> Result<Fftw<FftBuf<T>, FftBuf<C>>, FftwError> {
+    let mut new: Fftw<FftBuf<T>, FftBuf<C>> = if_ok!(Fftw::new_with(slice.len(), planner));
+    new.in_data.push_slice(slice);
+    Ok(new)
   }
 
   /// Prepare a new transform for 'capacity' elements.
   /// The transform can only be computed once the input buffer is full.
-  pub fn new(capacity: uint) -> Fftw<FftBuf<T>, FftBuf<Cmplx<f64>>> {
-    let mut _in: FftBuf<T> = TransformBuf::new(capacity);
+  pub fn new(capacity: uint) -> Fftw<FftBuf<T>, FftBuf<C>> {
+    estimated(Fftw::new_with(capacity, Estimate))
+  }
+
+  /// Same as `new`, planned with the options of 'planner'.
+  /// Returns `NoPlan` if fftw3 has no plan satisfying these options.
+  pub fn new_with<P: ToPlanner>(capacity: uint, planner: P)
+                                -> Result<Fftw<FftBuf<T>, FftBuf<C>>, FftwError> {
+    let _in: FftBuf<T> = TransformBuf::new(capacity);
     // When the input data is real the output buffer should have a n/2 + 1 capacity,
     // n otherwise. get_transformed_capacity returns the relevant value based on the
     // type of T.
-    let mut _out: FftBuf<Cmplx<f64>> = TransformBuf::new(_in.get_transformed_capacity());
-    let _p = _in.make_plan(_out.as_mut_slice());
-    Fftw {
+    let _out: FftBuf<C> = TransformBuf::new(_in.get_transformed_capacity());
+    let _p = if_ok!(Transform::plan(capacity, _in.data, _out.data, &planner.to_planner()));
+    Ok(Fftw {
       in_data: _in,
       out_data: _out,
       plan: _p,
-    }
+      shape: ~[capacity],
+      offsets: (0, 0),
+      rows: None,
+    })
   }
 }
 
-impl Fftw<FftBuf<Cmplx<f64>>, FftBuf<Cmplx<f64>>> {
+impl<F: FftwFloat> Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>> {
   /// Prepare a new transform from the given slice of numbers.
-  /// The elements of the slice are copied in an internal buffer allocated by fftw3.
+  /// The elements of the slice are copied in an internal buffer allocated by fftw3, once
+  /// the transform is planned.
   #[inline]
-  pub fn from_slice_inv(slice: &[Cmplx<f64>]) -> Fftw<FftBuf<Cmplx<f64>>, FftBuf<Cmplx<f64>>> {
-    let mut new = Fftw::new_inv(slice.len());
+  pub fn from_slice_inv(slice: &[Cmplx<F>]) -> Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>> {
+    estimated(Fftw::from_slice_inv_with(slice, Estimate))
+  }
+
+  /// Same as `from_slice_inv`, planned with the options of 'planner'.
+  /// Returns `NoPlan` if fftw3 has no plan satisfying these options.
+  #[inline]
+  pub fn from_slice_inv_with<P: ToPlanner>(slice: &[Cmplx<F>], planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let mut new = if_ok!(Fftw::new_inv_with(slice.len(), planner));
     new.in_data.push_slice(slice);
-    new
+    Ok(new)
   }
 
   /// Prepare a new inverse transform for 'capacity' elements.
   /// The transform can only be computed once the input buffer is full.
   /// For complex->real transform, the complex data should only have n/2+1
   /// elements (the first part of the Hermitian symmetry).
-  pub fn new_inv(capacity: uint) -> Fftw<FftBuf<Cmplx<f64>>, FftBuf<Cmplx<f64>>> {
-    let mut _out: FftBuf<Cmplx<f64>> = TransformBuf::new(capacity);
-    let mut _in: FftBuf<Cmplx<f64>> = TransformBuf::new(_out.get_transformed_capacity());
-    let _p = _out.make_plan_inv(_in.as_mut_slice());
-    Fftw {
+  pub fn new_inv(capacity: uint) -> Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>> {
+    estimated(Fftw::new_inv_with(capacity, Estimate))
+  }
+
+  /// Same as `new_inv`, planned with the options of 'planner'.
+  /// Returns `NoPlan` if fftw3 has no plan satisfying these options.
+  pub fn new_inv_with<P: ToPlanner>(capacity: uint, planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let _out: FftBuf<Cmplx<F>> = TransformBuf::new(capacity);
+    let _in: FftBuf<Cmplx<F>> = TransformBuf::new(_out.get_transformed_capacity());
+    let planner = planner.to_planner();
+    let _p = if_ok!(Transform::plan_inv(capacity, _in.data, _out.data, &planner));
+    Ok(Fftw {
       in_data: _in,
       out_data: _out,
       plan: _p,
+      shape: ~[capacity],
+      offsets: (0, 0),
+      rows: None,
+    })
+  }
+
+  /// Prepare a new 2d transform over a 'rows' x 'cols' array, stored in row-major order.
+  /// Returns an error if one of the dimensions is zero.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_2d(rows: uint, cols: uint)
+                -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_2d_with(rows, cols, Estimate)
+  }
+
+  /// Same as `new_2d`, planned with the options of 'planner'.
+  pub fn new_2d_with<P: ToPlanner>(rows: uint, cols: uint, planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let shape = ~[rows, cols];
+    if_ok!(check_shape(shape));
+    Fftw::new_dft(shape, FFTW_FORWARD, planner)
+  }
+
+  /// Prepare a new 2d inverse transform over a 'rows' x 'cols' array, stored in
+  /// row-major order.
+  /// Returns an error if one of the dimensions is zero.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_2d_inv(rows: uint, cols: uint)
+                    -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_2d_inv_with(rows, cols, Estimate)
+  }
+
+  /// Same as `new_2d_inv`, planned with the options of 'planner'.
+  pub fn new_2d_inv_with<P: ToPlanner>(rows: uint, cols: uint, planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let shape = ~[rows, cols];
+    if_ok!(check_shape(shape));
+    Fftw::new_dft(shape, FFTW_BACKWARD, planner)
+  }
+
+  /// Prepare a new 3d transform over a 'n0' x 'n1' x 'n2' array, stored in row-major
+  /// order.
+  /// Returns an error if one of the dimensions is zero.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_3d(n0: uint, n1: uint, n2: uint)
+                -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_3d_with(n0, n1, n2, Estimate)
+  }
+
+  /// Same as `new_3d`, planned with the options of 'planner'.
+  pub fn new_3d_with<P: ToPlanner>(n0: uint, n1: uint, n2: uint, planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let shape = ~[n0, n1, n2];
+    if_ok!(check_shape(shape));
+    Fftw::new_dft(shape, FFTW_FORWARD, planner)
+  }
+
+  /// Prepare a new 3d inverse transform over a 'n0' x 'n1' x 'n2' array, stored in
+  /// row-major order.
+  /// Returns an error if one of the dimensions is zero.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_3d_inv(n0: uint, n1: uint, n2: uint)
+                    -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_3d_inv_with(n0, n1, n2, Estimate)
+  }
+
+  /// Same as `new_3d_inv`, planned with the options of 'planner'.
+  pub fn new_3d_inv_with<P: ToPlanner>(n0: uint, n1: uint, n2: uint, planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let shape = ~[n0, n1, n2];
+    if_ok!(check_shape(shape));
+    Fftw::new_dft(shape, FFTW_BACKWARD, planner)
+  }
+
+  /// Prepare a new transform over an array of arbitrary rank, stored in row-major order.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_nd(shape: &[uint])
+                -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_nd_with(shape, Estimate)
+  }
+
+  /// Same as `new_nd`, planned with the options of 'planner'.
+  pub fn new_nd_with<P: ToPlanner>(shape: &[uint], planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    if_ok!(check_shape(shape));
+    Fftw::new_dft(shape.to_owned(), FFTW_FORWARD, planner)
+  }
+
+  /// Prepare a new inverse transform over an array of arbitrary rank, stored in
+  /// row-major order.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_nd_inv(shape: &[uint])
+                    -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_nd_inv_with(shape, Estimate)
+  }
+
+  /// Same as `new_nd_inv`, planned with the options of 'planner'.
+  pub fn new_nd_inv_with<P: ToPlanner>(shape: &[uint], planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    if_ok!(check_shape(shape));
+    Fftw::new_dft(shape.to_owned(), FFTW_BACKWARD, planner)
+  }
+
+  /// Prepare a new transform over an array of arbitrary rank from the given slice of
+  /// numbers, stored in row-major order. The length of the slice must be the product
+  /// of the dimensions.
+  /// The elements of the slice are copied in an internal buffer allocated by fftw3, once
+  /// the transform is planned.
+  pub fn from_slice_nd(shape: &[uint], slice: &[Cmplx<F>])
+                       -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::from_slice_nd_with(shape, slice, Estimate)
+  }
+
+  /// Same as `from_slice_nd`, planned with the options of 'planner'.
+  pub fn from_slice_nd_with<P: ToPlanner>(shape: &[uint], slice: &[Cmplx<F>], planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let size = if_ok!(check_shape(shape));
+    if size != slice.len() {
+      return Err(SizeMismatch(size, slice.len()));
     }
+    let mut new = if_ok!(Fftw::new_dft(shape.to_owned(), FFTW_FORWARD, planner));
+    new.in_data.push_slice(slice);
+    Ok(new)
+  }
+
+  /// Prepare a new inverse transform over an array of arbitrary rank from the given
+  /// slice of numbers, stored in row-major order. The length of the slice must be the
+  /// product of the dimensions.
+  /// The elements of the slice are copied in an internal buffer allocated by fftw3, once
+  /// the transform is planned.
+  pub fn from_slice_nd_inv(shape: &[uint], slice: &[Cmplx<F>])
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::from_slice_nd_inv_with(shape, slice, Estimate)
+  }
+
+  /// Same as `from_slice_nd_inv`, planned with the options of 'planner'.
+  pub fn from_slice_nd_inv_with<P: ToPlanner>(shape: &[uint], slice: &[Cmplx<F>],
+                                              planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let size = if_ok!(check_shape(shape));
+    if size != slice.len() {
+      return Err(SizeMismatch(size, slice.len()));
+    }
+    let mut new = if_ok!(Fftw::new_dft(shape.to_owned(), FFTW_BACKWARD, planner));
+    new.in_data.push_slice(slice);
+    Ok(new)
+  }
+
+  fn new_dft<P: ToPlanner>(shape: ~[uint], sign: c_int, planner: P)
+                           -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let capacity = shape.iter().fold(1u, |a, &b| a * b);
+    let _in: FftBuf<Cmplx<F>> = TransformBuf::new(capacity);
+    let _out: FftBuf<Cmplx<F>> = TransformBuf::new(capacity);
+    let _p = if_ok!(plan_dft(shape, _in.data, _out.data, sign, &planner.to_planner()));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: shape,
+      offsets: (0, 0),
+      rows: None,
+    })
+  }
+}
+
+/// Creates a complex to complex plan over an array of the given shape, stored in
+/// row-major order. Every planner call of the crate goes through the plan cache of the
+/// planner, which takes the lock and sets up the planner with `prepare` under the lock.
+/// These ones also go through the plan memo once enabled. The transforms of zero values
+/// get a plan computing nothing, as fftw3 does not plan them.
+fn plan_dft<F: FftwFloat>(shape: &[uint], input: *mut Cmplx<F>, output: *mut Cmplx<F>,
+                          sign: c_int, planner: &Planner) -> Result<Plan, FftwError> {
+  let dims: ~[c_int] = shape.iter().map(|&n| n as c_int).collect();
+  let kind = if sign == FFTW_FORWARD { "forward" } else { "backward" };
+  let transform = describe(kind, shape, input as uint == output as uint);
+  let precision = precision_of::<F>();
+  if shape.contains(&0) {
+    return Ok(Plan::empty(precision));
+  }
+  plan_memoized(planner, precision, transform, input, output, |flags| unsafe {
+    FftwFloat::plan_dft(dims.len() as c_int, dims.as_ptr(), input, output, sign, flags)
+  })
+}
+
+/// Creates a real to complex plan over an array of the given shape, stored in
+/// row-major order.
+fn plan_dft_r2c<F: FftwFloat>(shape: &[uint], input: *mut F, output: *mut Cmplx<F>,
+                              planner: &Planner) -> Result<Plan, FftwError> {
+  let dims: ~[c_int] = shape.iter().map(|&n| n as c_int).collect();
+  let transform = describe("r2c", shape, input as uint == output as uint);
+  let precision = precision_of::<F>();
+  if shape.contains(&0) {
+    return Ok(Plan::empty(precision));
+  }
+  plan_memoized(planner, precision, transform, input, output, |flags| unsafe {
+    FftwFloat::plan_dft_r2c(dims.len() as c_int, dims.as_ptr(), input, output, flags)
+  })
+}
+
+/// Creates a complex to real plan over an array of the given shape, stored in
+/// row-major order.
+fn plan_dft_c2r<F: FftwFloat>(shape: &[uint], input: *mut Cmplx<F>, output: *mut F,
+                              planner: &Planner) -> Result<Plan, FftwError> {
+  let dims: ~[c_int] = shape.iter().map(|&n| n as c_int).collect();
+  let transform = describe("c2r", shape, input as uint == output as uint);
+  let precision = precision_of::<F>();
+  if shape.contains(&0) {
+    return Ok(Plan::empty(precision));
+  }
+  plan_memoized(planner, precision, transform, input, output, |flags| unsafe {
+    FftwFloat::plan_dft_c2r(dims.len() as c_int, dims.as_ptr(), input, output, flags)
+  })
+}
+
+/// Returns the shape of the complex side of a real transform over 'shape' : only the
+/// n/2+1 first values of the last dimension are stored, the others being given by the
+/// Hermitian symmetry.
+fn half_shape(shape: &[uint]) -> ~[uint] {
+  let mut half = shape.to_owned();
+  let last = half.len() - 1;
+  half[last] = half[last]/2 + 1;
+  half
+}
+
+/// Checks that a transform can be planned over 'shape', and returns the number of
+/// elements of the transformed array.
+fn check_shape(shape: &[uint]) -> Result<uint, FftwError> {
+  if shape.is_empty() {
+    return Err(ZeroRank);
+  }
+  match shape.iter().position(|&n| n == 0) {
+    Some(axis) => Err(ZeroDimension(axis)),
+    None => Ok(shape.iter().fold(1u, |a, &b| a * b)),
+  }
+}
+
+/// Returns the precision of the transforms over values of type F.
+fn precision_of<F: FftwFloat>() -> Precision {
+  let real: &[F] = &[];
+  TransformData::precision(real)
+}
+
+/// Returns the plan made by a planner of fftw3, or `NoPlan` if it made none.
+fn check_plan(plan: Option<Plan>) -> Result<Plan, FftwError> {
+  match plan {
+    Some(plan) => Ok(plan),
+    None => Err(NoPlan),
+  }
+}
+
+/// Returns a transform planned with `Estimate` and the default options of the planner,
+/// which fftw3 always plans.
+fn estimated<T>(planned: Result<T, FftwError>) -> T {
+  match planned {
+    Ok(transform) => transform,
+    Err(err) => fail!("{}", err),
+  }
+}
+
+/// Returns the length of the contiguous rows of a buffer holding 'capacity' elements
+/// laid out along 'shape'. The last dimension is derived from the capacity since it
+/// is shorter on the complex side of a real transform.
+fn row_len(capacity: uint, shape: &[uint]) -> uint {
+  let lead = shape.init().iter().fold(1u, |a, &b| a * b);
+  if lead == 0 || capacity < lead {
+    1
+  } else {
+    capacity / lead
   }
 }
 
-impl Fftw<FftBuf<Cmplx<f64>>, FftBuf<f64>> {
+impl<F: FftwFloat> Fftw<FftBuf<F>, FftBuf<Cmplx<F>>> {
+  /// Prepare a new 2d transform over a 'rows' x 'cols' array of real values, stored in
+  /// row-major order. The output holds 'rows' x ('cols'/2+1) values.
+  /// Returns an error if one of the dimensions is zero.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_2d_r2c(rows: uint, cols: uint)
+                    -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_2d_r2c_with(rows, cols, Estimate)
+  }
+
+  /// Same as `new_2d_r2c`, planned with the options of 'planner'.
+  pub fn new_2d_r2c_with<P: ToPlanner>(rows: uint, cols: uint, planner: P)
+      -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    let shape = ~[rows, cols];
+    if_ok!(check_shape(shape));
+    Fftw::new_r2c(shape, planner)
+  }
+
+  /// Prepare a new 3d transform over a 'n0' x 'n1' x 'n2' array of real values, stored
+  /// in row-major order. The output holds 'n0' x 'n1' x ('n2'/2+1) values.
+  /// Returns an error if one of the dimensions is zero.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_3d_r2c(n0: uint, n1: uint, n2: uint)
+                    -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_3d_r2c_with(n0, n1, n2, Estimate)
+  }
+
+  /// Same as `new_3d_r2c`, planned with the options of 'planner'.
+  pub fn new_3d_r2c_with<P: ToPlanner>(n0: uint, n1: uint, n2: uint, planner: P)
+      -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    let shape = ~[n0, n1, n2];
+    if_ok!(check_shape(shape));
+    Fftw::new_r2c(shape, planner)
+  }
+
+  /// Prepare a new transform over an array of real values of arbitrary rank, stored in
+  /// row-major order. Only the n/2+1 first values of the last dimension are computed.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_nd_r2c(shape: &[uint])
+                    -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_nd_r2c_with(shape, Estimate)
+  }
+
+  /// Same as `new_nd_r2c`, planned with the options of 'planner'.
+  pub fn new_nd_r2c_with<P: ToPlanner>(shape: &[uint], planner: P)
+      -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    if_ok!(check_shape(shape));
+    Fftw::new_r2c(shape.to_owned(), planner)
+  }
+
+  fn new_r2c<P: ToPlanner>(shape: ~[uint], planner: P)
+                           -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    let capacity = shape.iter().fold(1u, |a, &b| a * b);
+    let half = half_shape(shape).iter().fold(1u, |a, &b| a * b);
+    let _in: FftBuf<F> = TransformBuf::new(capacity);
+    let _out: FftBuf<Cmplx<F>> = TransformBuf::new(half);
+    let _p = if_ok!(plan_dft_r2c(shape, _in.data, _out.data, &planner.to_planner()));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: shape,
+      offsets: (0, 0),
+      rows: None,
+    })
+  }
+}
+
+impl<F: FftwFloat> Fftw<FftBuf<Cmplx<F>>, FftBuf<F>> {
   /// Prepare a new transform from the given slice of numbers.
-  /// The elements of the slice are copied in an internal buffer allocated by fftw3.
+  /// The elements of the slice are copied in an internal buffer allocated by fftw3, once
+  /// the transform is planned.
+  #[inline]
+  pub fn from_slice_c2r(slice: &[Cmplx<F>]) -> Fftw<FftBuf<Cmplx<F>>, FftBuf<F>> {
+    estimated(Fftw::from_slice_c2r_with(slice, Estimate))
+  }
+
+  /// Same as `from_slice_c2r`, planned with the options of 'planner'.
+  /// Returns `NoPlan` if fftw3 has no plan satisfying these options.
   #[inline]
-  pub fn from_slice_c2r(slice: &[Cmplx<f64>]) -> Fftw<FftBuf<Cmplx<f64>>, FftBuf<f64>> {
-    let mut new = Fftw::new_c2r(slice.len());
+  pub fn from_slice_c2r_with<P: ToPlanner>(slice: &[Cmplx<F>], planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    let mut new = if_ok!(Fftw::new_c2r_with(slice.len(), planner));
     new.in_data.push_slice(slice);
-    new
+    Ok(new)
   }
 
 
@@ -434,15 +1292,94 @@ impl Fftw<FftBuf<Cmplx<f64>>, FftBuf<f64>> {
   /// The transform can only be computed once the input buffer is full.
   /// For complex->real transform, the complex data should only have n/2+1
   /// elements (the first part of the Hermitian symmetry).
-  pub fn new_c2r(capacity: uint) -> Fftw<FftBuf<Cmplx<f64>>, FftBuf<f64>> {
-    let mut _out: FftBuf<f64> = TransformBuf::new(capacity);
-    let mut _in: FftBuf<Cmplx<f64>> = TransformBuf::new(_out.get_transformed_capacity());
-    let _p = _out.make_plan_inv(_in.as_mut_slice());
-    Fftw {
+  pub fn new_c2r(capacity: uint) -> Fftw<FftBuf<Cmplx<F>>, FftBuf<F>> {
+    estimated(Fftw::new_c2r_with(capacity, Estimate))
+  }
+
+  /// Same as `new_c2r`, planned with the options of 'planner'.
+  /// Returns `NoPlan` if fftw3 has no plan satisfying these options.
+  pub fn new_c2r_with<P: ToPlanner>(capacity: uint, planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    let _out: FftBuf<F> = TransformBuf::new(capacity);
+    let _in: FftBuf<Cmplx<F>> = TransformBuf::new(_out.get_transformed_capacity());
+    let planner = planner.to_planner();
+    let _p = if_ok!(Transform::plan_inv(capacity, _in.data, _out.data, &planner));
+    Ok(Fftw {
       in_data: _in,
       out_data: _out,
       plan: _p,
-    }
+      shape: ~[capacity],
+      offsets: (0, 0),
+      rows: None,
+    })
+  }
+
+  /// Prepare a new 2d inverse transform producing a 'rows' x 'cols' array of real
+  /// values, stored in row-major order. The input holds 'rows' x ('cols'/2+1) values.
+  /// Returns an error if one of the dimensions is zero.
+  /// Computing a multi-dimensional complex->real transform overwrites its input.
+  pub fn new_2d_c2r(rows: uint, cols: uint)
+                    -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    Fftw::new_2d_c2r_with(rows, cols, Estimate)
+  }
+
+  /// Same as `new_2d_c2r`, planned with the options of 'planner'.
+  pub fn new_2d_c2r_with<P: ToPlanner>(rows: uint, cols: uint, planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    let shape = ~[rows, cols];
+    if_ok!(check_shape(shape));
+    Fftw::new_c2r_shape(shape, planner)
+  }
+
+  /// Prepare a new 3d inverse transform producing a 'n0' x 'n1' x 'n2' array of real
+  /// values, stored in row-major order. The input holds 'n0' x 'n1' x ('n2'/2+1) values.
+  /// Returns an error if one of the dimensions is zero.
+  /// Computing a multi-dimensional complex->real transform overwrites its input.
+  pub fn new_3d_c2r(n0: uint, n1: uint, n2: uint)
+                    -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    Fftw::new_3d_c2r_with(n0, n1, n2, Estimate)
+  }
+
+  /// Same as `new_3d_c2r`, planned with the options of 'planner'.
+  pub fn new_3d_c2r_with<P: ToPlanner>(n0: uint, n1: uint, n2: uint, planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    let shape = ~[n0, n1, n2];
+    if_ok!(check_shape(shape));
+    Fftw::new_c2r_shape(shape, planner)
+  }
+
+  /// Prepare a new inverse transform producing an array of real values of arbitrary
+  /// rank, stored in row-major order. The input only holds the n/2+1 first values of
+  /// the last dimension.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  /// Computing a multi-dimensional complex->real transform overwrites its input.
+  pub fn new_nd_c2r(shape: &[uint])
+                    -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    Fftw::new_nd_c2r_with(shape, Estimate)
+  }
+
+  /// Same as `new_nd_c2r`, planned with the options of 'planner'.
+  pub fn new_nd_c2r_with<P: ToPlanner>(shape: &[uint], planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    if_ok!(check_shape(shape));
+    Fftw::new_c2r_shape(shape.to_owned(), planner)
+  }
+
+  fn new_c2r_shape<P: ToPlanner>(shape: ~[uint], planner: P)
+                                 -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    let capacity = shape.iter().fold(1u, |a, &b| a * b);
+    let half = half_shape(shape).iter().fold(1u, |a, &b| a * b);
+    let _in: FftBuf<Cmplx<F>> = TransformBuf::new(half);
+    let _out: FftBuf<F> = TransformBuf::new(capacity);
+    let _p = if_ok!(plan_dft_c2r(shape, _in.data, _out.data, &planner.to_planner()));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: shape,
+      offsets: (0, 0),
+      rows: None,
+    })
   }
 }
 
@@ -454,10 +1391,11 @@ impl<Tin: TransformData, In: TransformBuf<Tin>,
   /// get by calling the output() method)
   pub fn compute<'a>(&'a mut self) -> Option<&'a[Tout]> {
     if self.in_data.ready() {
-      unsafe{
-        fftw_execute(self.plan);
-      }
       self.out_data.mark_filled();
+      unsafe {
+        self.plan.execute_on(self.in_data.as_mut_slice(), self.out_data.as_mut_slice(),
+                             self.offsets);
+      }
       Some(self.out_data.as_slice())
     } else {
       None
@@ -489,14 +1427,98 @@ impl<Tin: TransformData, In: TransformBuf<Tin>,
   }
 }
 
-#[unsafe_destructor]
-impl<In, Out> Drop for Fftw<In, Out> {
-  fn drop(&mut self) {
+impl<Tin: TransformData, Tout: TransformData> Fftw<FftBuf<Tin>, FftBuf<Tout>> {
+  /// Returns a mutable ref to the input buffer. You can use this to
+  /// add/remove elements from the input.
+  #[inline]
+  pub fn ref_input<'a>(&'a mut self) -> &'a mut FftBuf<Tin> {
+    &mut self.in_data
+  }
+
+  #[inline]
+  /// Returns the dimensions of the transform, in row-major order.
+  pub fn shape<'a>(&'a self) -> &'a [uint] {
+    self.shape.as_slice()
+  }
+
+  /// Returns the dimensions of the input array. They only differ from the shape
+  /// of the transform on the complex side of a real transform, where the last
+  /// dimension holds n/2+1 values. For a batch, they are the number and the length
+  /// of the rows returned by `input_rows`.
+  pub fn input_shape(&self) -> ~[uint] {
+    let (row, _) = self.row_lens();
+    self.buffer_shape(self.in_data.capacity, row)
+  }
+
+  /// Returns the dimensions of the output array. They only differ from the shape
+  /// of the transform on the complex side of a real transform, where the last
+  /// dimension holds n/2+1 values. For a batch, they are the number and the length
+  /// of the rows returned by `output_rows`.
+  pub fn output_shape(&self) -> ~[uint] {
+    let (_, row) = self.row_lens();
+    self.buffer_shape(self.out_data.capacity, row)
+  }
+
+  /// Returns the length of the rows of the input and output buffers.
+  fn row_lens(&self) -> (uint, uint) {
+    match self.rows {
+      Some(rows) => rows,
+      None => (row_len(self.in_data.capacity, self.shape),
+               row_len(self.out_data.capacity, self.shape)),
+    }
+  }
+
+  /// Returns the dimensions of a buffer holding 'capacity' elements in rows of 'row'
+  /// elements.
+  fn buffer_shape(&self, capacity: uint, row: uint) -> ~[uint] {
+    match self.rows {
+      Some(_) => ~[(capacity + row - 1) / row, row],
+      None => {
+        let mut shape = self.shape.clone();
+        let last = shape.len() - 1;
+        shape[last] = row;
+        shape
+      }
+    }
+  }
+
+  /// Compute the transform of 'input' into 'output', with the plan of this transform.
+  /// The internal buffers are left untouched, so this allows reusing a plan on many
+  /// arrays, for instance other `FftBuf`s of the same capacity.
+  /// The arrays must have the length of the internal buffers, and the same alignment
+  /// (arrays allocated by fftw3 are always properly aligned). As for the internal
+  /// buffers, a complex to real transform overwrites its input.
+  pub fn compute_with(&self, input: &mut [Tin], output: &mut [Tout]) -> Result<(), FftwError> {
+    if_ok!(self.planned_arrays().check(input, output));
     unsafe {
-      let _g = LOCK.lock();
-      fftw_destroy_plan(self.plan);
+      self.plan.execute_with(input, output, self.offsets);
+    }
+    Ok(())
+  }
+
+  /// Returns the arrays the plan of this transform was made for.
+  fn planned_arrays(&self) -> PlannedArrays {
+    unsafe {
+      PlannedArrays::new(self.in_data.data, self.in_data.capacity, self.out_data.data,
+                         self.out_data.capacity, self.offsets)
     }
   }
+
+  /// Creates an iterator over the rows of the input data. The values are stored in
+  /// row-major order, the last dimension of the transform being the contiguous one.
+  /// For a batch, see `new_many` for the layout of the rows.
+  pub fn input_rows<'a>(&'a self) -> Chunks<'a, Tin> {
+    let (row, _) = self.row_lens();
+    self.in_data.as_slice().chunks(row)
+  }
+
+  /// Creates an iterator over the rows of the output data. The values are stored in
+  /// row-major order, the last dimension of the transform being the contiguous one.
+  /// For a batch, see `new_many` for the layout of the rows.
+  pub fn output_rows<'a>(&'a self) -> Chunks<'a, Tout> {
+    let (_, row) = self.row_lens();
+    self.out_data.as_slice().chunks(row)
+  }
 }
 
 impl<T: TransformData> Index<uint, Option<T>> for FftBuf<T> {
@@ -526,17 +1548,18 @@ impl<T: TransformData> Vector<T> for FftBuf<T> {
 
 pub mod iteration {
   use num::complex::Cmplx;
+  use super::FftwFloat;
   /// Iterator over *all* the values of a result of a transform over real data.
-  pub struct HermitianItems<'a> {
-    priv ptr: *Cmplx<f64>,
-    priv start: *Cmplx<f64>,
-    priv end: *Cmplx<f64>,
+  pub struct HermitianItems<'a, F> {
+    priv ptr: *Cmplx<F>,
+    priv start: *Cmplx<F>,
+    priv end: *Cmplx<F>,
     priv dir: i8, // holds the information about the direction we are iterating in
                   // and whether there should be an even or odd number of elements.
   }
 
-  impl<'a> Iterator<Cmplx<f64>> for HermitianItems<'a> {
-    fn next(&mut self) -> Option<Cmplx<f64>> {
+  impl<'a, F: FftwFloat> Iterator<Cmplx<F>> for HermitianItems<'a, F> {
+    fn next(&mut self) -> Option<Cmplx<F>> {
       if self.dir > 0 {
         if self.ptr >= self.end {
           let tmp = self.end;
@@ -565,19 +1588,22 @@ pub mod iteration {
           unsafe {
             let res = *self.ptr;
             self.ptr = self.ptr.offset(-1);
-            Some(res.conj())
+            Some(Cmplx {
+              re: res.re,
+              im: -res.im,
+            })
           }
         }
       }
     }
   }
 
-  impl super::Fftw<super::FftBuf<f64>, super::FftBuf<Cmplx<f64>>> {
+  impl<F: FftwFloat> super::Fftw<super::FftBuf<F>, super::FftBuf<Cmplx<F>>> {
     /// Creates an iterator over *all* the values of a result from a transform over reals.
     /// Since the result of a transform over real values is a Hermitian symmetric space,
     /// only the first half of the symmetry needs to be computed. This iterator will yield
     /// the values of the result and then the remaining symmetric values.
-    pub fn iter_symmetry<'a>(&'a self) -> HermitianItems<'a> {
+    pub fn iter_symmetry<'a>(&'a self) -> HermitianItems<'a, F> {
       unsafe {
         HermitianItems {
           ptr: &*self.out_data.data,
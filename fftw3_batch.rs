@@ -0,0 +1,232 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Batched transforms : a single plan computing the transforms of 'howmany' signals
+//! of the same length, stored in one buffer.
+//!
+//! The signals are laid out with a 'stride' between two consecutive values of a signal,
+//! and a 'dist' between the first values of two consecutive signals. Signals stored one
+//! after the other use a stride of 1 and a dist of n, interleaved signals use a stride
+//! of 'howmany' and a dist of 1.
+//!
+//! The shape of a batch is the shape of one of its transforms. The rows of its buffers
+//! follow the layout : signals stored one after the other give a row of 'dist' values
+//! per signal, interleaved signals give a row of 'stride' values per position in the
+//! signals, holding the values of every signal at that position.
+
+use num::complex::Cmplx;
+
+use fftw3_bindgen::{FFTW_FORWARD, FFTW_BACKWARD};
+
+use std::libc::c_int;
+use std::ptr::null;
+
+use super::{Fftw, FftBuf, FftwError, FftwFloat, TransformBuf, ZeroDimension, InvalidLayout,
+            EmptyBatch, Estimate, ToPlanner, precision_of};
+use fftw3_cache::{plan_cached, describe_layout};
+
+/// Position of a batch of signals in a buffer.
+struct Layout {
+  stride: uint,
+  dist: uint,
+  capacity: uint,
+  // Whether the values of the signals are interleaved rather than stored one signal
+  // after the other.
+  interleaved: bool,
+}
+
+impl Layout {
+  /// Checks that 'howmany' signals of length 'n' do not overlap when stored with the
+  /// given stride and dist.
+  fn new(n: uint, howmany: uint, stride: uint, dist: uint) -> Result<Layout, FftwError> {
+    if howmany == 0 {
+      return Err(EmptyBatch);
+    } else if n == 0 {
+      return Err(ZeroDimension(0));
+    } else if stride == 0 || dist == 0 || (dist < n*stride && stride < howmany*dist) {
+      return Err(InvalidLayout);
+    }
+
+    Ok(Layout {
+      stride: stride,
+      dist: dist,
+      capacity: (howmany - 1)*dist + (n - 1)*stride + 1,
+      interleaved: dist < n*stride,
+    })
+  }
+
+  /// Returns the length of the rows of a buffer holding the batch.
+  fn row_len(&self) -> uint {
+    if self.interleaved {
+      self.stride
+    } else {
+      self.dist
+    }
+  }
+
+  /// Returns the layout of the complex side of a batch of real transforms, where each
+  /// signal only holds n/2+1 values. Interleaved signals keep the same dist, signals
+  /// stored one after the other are packed.
+  fn half(&self, n: uint, howmany: uint) -> Layout {
+    let half = n/2 + 1;
+    let dist = if self.interleaved {
+      self.dist
+    } else {
+      half*self.stride
+    };
+    Layout {
+      stride: self.stride,
+      dist: dist,
+      capacity: (howmany - 1)*dist + (half - 1)*self.stride + 1,
+      interleaved: self.interleaved,
+    }
+  }
+}
+
+/// Returns the description of a batch of 'howmany' transforms of the given kind over 'n'
+/// values, read with the layout 'input' and written with the layout 'output'.
+fn describe_batch(kind: &str, n: uint, howmany: uint, input: &Layout,
+                  output: &Layout) -> ~str {
+  let layout = format!("{} signals, stride {}/{}, dist {}/{}", howmany, input.stride,
+                       output.stride, input.dist, output.dist);
+  describe_layout(kind, [n], layout, false)
+}
+
+impl<F: FftwFloat> Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>> {
+  /// Prepare a new transform over 'howmany' complex signals of length 'n', computed with a
+  /// single plan. The output signals are laid out like the input ones.
+  /// Returns an error if the signals overlap, if 'n' is zero or if the batch is empty.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_many(n: uint, howmany: uint, stride: uint, dist: uint)
+                  -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_many_with(n, howmany, stride, dist, Estimate)
+  }
+
+  /// Same as `new_many`, planned with the options of 'planner'.
+  pub fn new_many_with<P: ToPlanner>(n: uint, howmany: uint, stride: uint, dist: uint,
+                                     planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let layout = if_ok!(Layout::new(n, howmany, stride, dist));
+    Fftw::new_many_dft(n, howmany, layout, FFTW_FORWARD, planner)
+  }
+
+  /// Prepare a new inverse transform over 'howmany' complex signals of length 'n',
+  /// computed with a single plan. The output signals are laid out like the input ones.
+  /// Returns an error if the signals overlap, if 'n' is zero or if the batch is empty.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_many_inv(n: uint, howmany: uint, stride: uint, dist: uint)
+                      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_many_inv_with(n, howmany, stride, dist, Estimate)
+  }
+
+  /// Same as `new_many_inv`, planned with the options of 'planner'.
+  pub fn new_many_inv_with<P: ToPlanner>(n: uint, howmany: uint, stride: uint, dist: uint,
+                                         planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let layout = if_ok!(Layout::new(n, howmany, stride, dist));
+    Fftw::new_many_dft(n, howmany, layout, FFTW_BACKWARD, planner)
+  }
+
+  fn new_many_dft<P: ToPlanner>(n: uint, howmany: uint, layout: Layout, sign: c_int,
+                                planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let _in: FftBuf<Cmplx<F>> = TransformBuf::new(layout.capacity);
+    let _out: FftBuf<Cmplx<F>> = TransformBuf::new(layout.capacity);
+    let nc = n as c_int;
+    let kind = if sign == FFTW_FORWARD { "forward" } else { "backward" };
+    let transform = describe_batch(kind, n, howmany, &layout, &layout);
+    let _p = if_ok!(plan_cached(&planner.to_planner(), precision_of::<F>(), transform,
+                                |flags| unsafe {
+      FftwFloat::plan_many_dft(1, &nc, howmany as c_int,
+                               _in.data, null(), layout.stride as c_int, layout.dist as c_int,
+                               _out.data, null(), layout.stride as c_int, layout.dist as c_int,
+                               sign, flags)
+    }));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: ~[n],
+      offsets: (0, 0),
+      rows: Some((layout.row_len(), layout.row_len())),
+    })
+  }
+}
+
+impl<F: FftwFloat> Fftw<FftBuf<F>, FftBuf<Cmplx<F>>> {
+  /// Prepare a new transform over 'howmany' real signals of length 'n', computed with a
+  /// single plan. Each output signal holds n/2+1 values, with the same stride as the
+  /// input.
+  /// Returns an error if the signals overlap, if 'n' is zero or if the batch is empty.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_many_r2c(n: uint, howmany: uint, stride: uint, dist: uint)
+                      -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_many_r2c_with(n, howmany, stride, dist, Estimate)
+  }
+
+  /// Same as `new_many_r2c`, planned with the options of 'planner'.
+  pub fn new_many_r2c_with<P: ToPlanner>(n: uint, howmany: uint, stride: uint, dist: uint,
+                                         planner: P)
+      -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    let layout = if_ok!(Layout::new(n, howmany, stride, dist));
+    let half = layout.half(n, howmany);
+    let _in: FftBuf<F> = TransformBuf::new(layout.capacity);
+    let _out: FftBuf<Cmplx<F>> = TransformBuf::new(half.capacity);
+    let nc = n as c_int;
+    let transform = describe_batch("r2c", n, howmany, &layout, &half);
+    let _p = if_ok!(plan_cached(&planner.to_planner(), precision_of::<F>(), transform,
+                                |flags| unsafe {
+      FftwFloat::plan_many_dft_r2c(1, &nc, howmany as c_int,
+                                   _in.data, null(), layout.stride as c_int,
+                                   layout.dist as c_int,
+                                   _out.data, null(), half.stride as c_int, half.dist as c_int,
+                                   flags)
+    }));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: ~[n],
+      offsets: (0, 0),
+      rows: Some((layout.row_len(), half.row_len())),
+    })
+  }
+}
+
+impl<F: FftwFloat> Fftw<FftBuf<Cmplx<F>>, FftBuf<F>> {
+  /// Prepare a new inverse transform producing 'howmany' real signals of length 'n',
+  /// computed with a single plan. Each input signal holds n/2+1 values, with the same
+  /// stride as the output.
+  /// Returns an error if the signals overlap, if 'n' is zero or if the batch is empty.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_many_c2r(n: uint, howmany: uint, stride: uint, dist: uint)
+                      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    Fftw::new_many_c2r_with(n, howmany, stride, dist, Estimate)
+  }
+
+  /// Same as `new_many_c2r`, planned with the options of 'planner'.
+  pub fn new_many_c2r_with<P: ToPlanner>(n: uint, howmany: uint, stride: uint, dist: uint,
+                                         planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    let layout = if_ok!(Layout::new(n, howmany, stride, dist));
+    let half = layout.half(n, howmany);
+    let _in: FftBuf<Cmplx<F>> = TransformBuf::new(half.capacity);
+    let _out: FftBuf<F> = TransformBuf::new(layout.capacity);
+    let nc = n as c_int;
+    let transform = describe_batch("c2r", n, howmany, &half, &layout);
+    let _p = if_ok!(plan_cached(&planner.to_planner(), precision_of::<F>(), transform,
+                                |flags| unsafe {
+      FftwFloat::plan_many_dft_c2r(1, &nc, howmany as c_int,
+                                   _in.data, null(), half.stride as c_int, half.dist as c_int,
+                                   _out.data, null(), layout.stride as c_int,
+                                   layout.dist as c_int,
+                                   flags)
+    }));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: ~[n],
+      offsets: (0, 0),
+      rows: Some((half.row_len(), layout.row_len())),
+    })
+  }
+}
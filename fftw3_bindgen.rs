@@ -4,7 +4,7 @@
 #[cfg(target_arch = "x86_64")]
 use num::complex::Cmplx;
 
-use std::libc::{c_void, c_int, c_uint, c_float,
+use std::libc::{c_void, c_int, c_uint, c_float, c_char,
                 c_long, FILE, size_t, c_double};
 
 pub static FFTW_FORWARD: c_int = -1;
@@ -18,6 +18,7 @@ pub static FFTW_EXHSTIVE: c_uint = 1 << 3; /* NO_EXHSTIVE is default */
 pub static FFTW_PRESERVE_INT: c_uint = 1 << 4; /* cancels FFTW_DESTROY_INT */
 pub static FFTW_PATIENT: c_uint = 1 << 5; /* IMPATIENT is default */
 pub static FFTW_ESTIMATE: c_uint = 1 << 6;
+pub static FFTW_WISDOM_ONLY: c_uint = 1 << 21;
 
 pub static FFTW_R2HC: c_uint = 0;
 pub static FFTW_HC2R: c_uint = 1;
@@ -33,6 +34,11 @@ pub static FFTW_RODFT11: c_uint = 10;
 
 pub type ptrdiff_t = c_long;
 pub type wchar_t = c_int;
+pub type fftw_r2r_kind = c_uint;
+pub type fftw_write_char_func_do_not_use_me =
+    Option<extern "C" fn(c: c_char, arg1: *mut c_void)>;
+pub type fftw_read_char_func_do_not_use_me =
+    Option<extern "C" fn(arg1: *mut c_void) -> c_int>;
 
 #[cfg(target_arch = "x86_64")]
 pub type fftw_complex = Cmplx<f64>;
@@ -40,14 +46,61 @@ pub type fftw_complex = Cmplx<f64>;
 #[cfg(not(target_arch = "x86_64"))]
 pub type fftw_complex =[c_double, ..2u];
 
+pub struct Struct_fftw_iodim_do_not_use_me {
+    n: c_int,
+    is: c_int,
+    os: c_int,
+}
+pub type fftw_iodim = Struct_fftw_iodim_do_not_use_me;
+pub struct Struct_fftw_iodim64_do_not_use_me {
+    n: ptrdiff_t,
+    is: ptrdiff_t,
+    os: ptrdiff_t,
+}
+pub type fftw_iodim64 = Struct_fftw_iodim64_do_not_use_me;
+
 pub type Struct_fftw_plan_s = c_void;
 pub type fftw_plan = *mut Struct_fftw_plan_s;
+pub type fftw_write_char_func = fftw_write_char_func_do_not_use_me;
+pub type fftw_read_char_func = fftw_read_char_func_do_not_use_me;
+#[cfg(target_arch = "x86_64")]
+pub type fftwf_complex = Cmplx<f32>;
+
+#[cfg(not(target_arch = "x86_64"))]
 pub type fftwf_complex = [c_float, ..2u];
 pub type Struct_fftwf_plan_s = c_void;
 pub type fftwf_plan = *mut Struct_fftwf_plan_s;
-pub type fftwl_complex = [c_double, ..2u];
+pub type fftwf_write_char_func = fftw_write_char_func_do_not_use_me;
+pub type fftwf_read_char_func = fftw_read_char_func_do_not_use_me;
+/// C long double, the 80-bit extended precision type of x86_64 stored on 16 bytes.
+/// fftw3 only takes pointers to it, so its content is left opaque. The long double of
+/// the other targets has another layout, their extended precision is not bound.
+#[cfg(target_arch = "x86_64")]
+pub type c_long_double = [u8, ..16u];
+#[cfg(target_arch = "x86_64")]
+pub type fftwl_complex = [c_long_double, ..2u];
+#[cfg(target_arch = "x86_64")]
 pub type Struct_fftwl_plan_s = c_void;
+#[cfg(target_arch = "x86_64")]
 pub type fftwl_plan = *mut Struct_fftwl_plan_s;
+#[cfg(target_arch = "x86_64")]
+pub type fftwl_write_char_func = fftw_write_char_func_do_not_use_me;
+#[cfg(target_arch = "x86_64")]
+pub type fftwl_read_char_func = fftw_read_char_func_do_not_use_me;
+/// __float128, the IEEE 754 quadruple precision type of libquadmath. As for
+/// long double, fftw3 only takes pointers to it.
+#[cfg(fftw3q)]
+pub type __float128 = [u8, ..16u];
+#[cfg(fftw3q)]
+pub type fftwq_complex = [__float128, ..2u];
+#[cfg(fftw3q)]
+pub type Struct_fftwq_plan_s = c_void;
+#[cfg(fftw3q)]
+pub type fftwq_plan = *mut Struct_fftwq_plan_s;
+#[cfg(fftw3q)]
+pub type fftwq_write_char_func = fftw_write_char_func_do_not_use_me;
+#[cfg(fftw3q)]
+pub type fftwq_read_char_func = fftw_read_char_func_do_not_use_me;
 #[link(name = "fftw3")]
 extern "C" {
     pub fn fftw_execute(p: fftw_plan);
@@ -117,22 +170,154 @@ extern "C" {
                                       ro: *mut c_double, io: *mut c_double);
     pub fn fftw_execute_split_dft_c2r(p: fftw_plan, ri: *mut c_double,
                                       ii: *mut c_double, out: *mut c_double);
+    pub fn fftw_plan_r2r(rank: c_int, n: *c_int, _in: *mut c_double,
+                         out: *mut c_double, kind: *fftw_r2r_kind,
+                         flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_r2r_1d(n: c_int, _in: *mut c_double, out: *mut c_double,
+                            kind: fftw_r2r_kind, flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_r2r_2d(n0: c_int, n1: c_int, _in: *mut c_double,
+                            out: *mut c_double, kind0: fftw_r2r_kind,
+                            kind1: fftw_r2r_kind, flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_r2r_3d(n0: c_int, n1: c_int, n2: c_int,
+                            _in: *mut c_double, out: *mut c_double,
+                            kind0: fftw_r2r_kind, kind1: fftw_r2r_kind,
+                            kind2: fftw_r2r_kind, flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_many_r2r(rank: c_int, n: *c_int, howmany: c_int,
+                              _in: *mut c_double, inembed: *c_int,
+                              istride: c_int, idist: c_int,
+                              out: *mut c_double, onembed: *c_int,
+                              ostride: c_int, odist: c_int,
+                              kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftw_plan;
+    pub fn fftw_plan_guru_dft(rank: c_int, dims: *fftw_iodim,
+                              howmany_rank: c_int, howmany_dims: *fftw_iodim,
+                              _in: *mut fftw_complex, out: *mut fftw_complex,
+                              sign: c_int, flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_guru_split_dft(rank: c_int, dims: *fftw_iodim,
+                                    howmany_rank: c_int,
+                                    howmany_dims: *fftw_iodim,
+                                    ri: *mut c_double, ii: *mut c_double,
+                                    ro: *mut c_double, io: *mut c_double,
+                                    flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_guru_dft_r2c(rank: c_int, dims: *fftw_iodim,
+                                  howmany_rank: c_int,
+                                  howmany_dims: *fftw_iodim,
+                                  _in: *mut c_double, out: *mut fftw_complex,
+                                  flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_guru_split_dft_r2c(rank: c_int, dims: *fftw_iodim,
+                                        howmany_rank: c_int,
+                                        howmany_dims: *fftw_iodim,
+                                        _in: *mut c_double, ro: *mut c_double,
+                                        io: *mut c_double, flags: c_uint) ->
+     fftw_plan;
+    pub fn fftw_plan_guru_dft_c2r(rank: c_int, dims: *fftw_iodim,
+                                  howmany_rank: c_int,
+                                  howmany_dims: *fftw_iodim,
+                                  _in: *mut fftw_complex, out: *mut c_double,
+                                  flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_guru_split_dft_c2r(rank: c_int, dims: *fftw_iodim,
+                                        howmany_rank: c_int,
+                                        howmany_dims: *fftw_iodim,
+                                        ri: *mut c_double, ii: *mut c_double,
+                                        out: *mut c_double, flags: c_uint) ->
+     fftw_plan;
+    pub fn fftw_plan_guru_r2r(rank: c_int, dims: *fftw_iodim,
+                              howmany_rank: c_int, howmany_dims: *fftw_iodim,
+                              _in: *mut c_double, out: *mut c_double,
+                              kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftw_plan;
+    pub fn fftw_plan_guru64_dft(rank: c_int, dims: *fftw_iodim64,
+                                howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64,
+                                _in: *mut fftw_complex,
+                                out: *mut fftw_complex, sign: c_int,
+                                flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_guru64_split_dft(rank: c_int, dims: *fftw_iodim64,
+                                      howmany_rank: c_int,
+                                      howmany_dims: *fftw_iodim64,
+                                      ri: *mut c_double, ii: *mut c_double,
+                                      ro: *mut c_double, io: *mut c_double,
+                                      flags: c_uint) -> fftw_plan;
+    pub fn fftw_plan_guru64_dft_r2c(rank: c_int, dims: *fftw_iodim64,
+                                    howmany_rank: c_int,
+                                    howmany_dims: *fftw_iodim64,
+                                    _in: *mut c_double,
+                                    out: *mut fftw_complex, flags: c_uint) ->
+     fftw_plan;
+    pub fn fftw_plan_guru64_split_dft_r2c(rank: c_int, dims: *fftw_iodim64,
+                                          howmany_rank: c_int,
+                                          howmany_dims: *fftw_iodim64,
+                                          _in: *mut c_double,
+                                          ro: *mut c_double,
+                                          io: *mut c_double, flags: c_uint) ->
+     fftw_plan;
+    pub fn fftw_plan_guru64_dft_c2r(rank: c_int, dims: *fftw_iodim64,
+                                    howmany_rank: c_int,
+                                    howmany_dims: *fftw_iodim64,
+                                    _in: *mut fftw_complex,
+                                    out: *mut c_double, flags: c_uint) ->
+     fftw_plan;
+    pub fn fftw_plan_guru64_split_dft_c2r(rank: c_int, dims: *fftw_iodim64,
+                                          howmany_rank: c_int,
+                                          howmany_dims: *fftw_iodim64,
+                                          ri: *mut c_double,
+                                          ii: *mut c_double,
+                                          out: *mut c_double, flags: c_uint)
+     -> fftw_plan;
+    pub fn fftw_plan_guru64_r2r(rank: c_int, dims: *fftw_iodim64,
+                                howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64,
+                                _in: *mut c_double, out: *mut c_double,
+                                kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftw_plan;
+    pub fn fftw_execute_r2r(p: fftw_plan, _in: *mut c_double,
+                            out: *mut c_double);
     pub fn fftw_destroy_plan(p: fftw_plan);
+    pub fn fftw_forget_wisdom();
     pub fn fftw_cleanup();
     pub fn fftw_set_timelimit(t: c_double);
-    pub fn fftw_plan_with_nthreads(nthreads: c_int);
-    pub fn fftw_init_threads() -> c_int;
-    pub fn fftw_cleanup_threads();
+    pub fn fftw_export_wisdom_to_filename(filename: *c_char) -> c_int;
+    pub fn fftw_export_wisdom_to_file(output_file: *mut FILE);
+    pub fn fftw_export_wisdom_to_string() -> *mut c_char;
+    pub fn fftw_export_wisdom(write_char: fftw_write_char_func,
+                              data: *mut c_void);
+    pub fn fftw_import_system_wisdom() -> c_int;
+    pub fn fftw_import_wisdom_from_filename(filename: *c_char) -> c_int;
+    pub fn fftw_import_wisdom_from_file(input_file: *mut FILE) -> c_int;
+    pub fn fftw_import_wisdom_from_string(input_string: *c_char) -> c_int;
+    pub fn fftw_import_wisdom(read_char: fftw_read_char_func,
+                              data: *mut c_void) -> c_int;
     pub fn fftw_fprint_plan(p: fftw_plan, output_file: *mut FILE);
     pub fn fftw_print_plan(p: fftw_plan);
     pub fn fftw_malloc(n: size_t) -> *mut c_void;
     pub fn fftw_alloc_real(n: size_t) -> *mut c_double;
     pub fn fftw_alloc_complex(n: size_t) -> *mut fftw_complex;
     pub fn fftw_free(p: *mut c_void);
+    pub fn fftw_alignment_of(p: *mut c_double) -> c_int;
     pub fn fftw_flops(p: fftw_plan, add: *mut c_double, mul: *mut c_double,
                       fmas: *mut c_double);
     pub fn fftw_estimate_cost(p: fftw_plan) -> c_double;
     pub fn fftw_cost(p: fftw_plan) -> c_double;
+    pub static fftw_version: [c_char, ..0u];
+    pub static fftw_cc: [c_char, ..0u];
+    pub static fftw_codelet_optim: [c_char, ..0u];
+}
+#[cfg(fftw3_threads, not(fftw3_omp))]
+#[link(name = "fftw3_threads")]
+extern "C" {
+    pub fn fftw_plan_with_nthreads(nthreads: c_int);
+    pub fn fftw_init_threads() -> c_int;
+    pub fn fftw_cleanup_threads();
+}
+#[cfg(fftw3_threads, fftw3_omp)]
+#[link(name = "fftw3_omp")]
+extern "C" {
+    pub fn fftw_plan_with_nthreads(nthreads: c_int);
+    pub fn fftw_init_threads() -> c_int;
+    pub fn fftw_cleanup_threads();
+}
+#[link(name = "fftw3f")]
+extern "C" {
     pub fn fftwf_execute(p: fftwf_plan);
     pub fn fftwf_plan_dft(rank: c_int, n: *c_int, _in: *mut fftwf_complex,
                           out: *mut fftwf_complex, sign: c_int, flags: c_uint)
@@ -200,22 +385,157 @@ extern "C" {
                                        ro: *mut c_float, io: *mut c_float);
     pub fn fftwf_execute_split_dft_c2r(p: fftwf_plan, ri: *mut c_float,
                                        ii: *mut c_float, out: *mut c_float);
+    pub fn fftwf_plan_r2r(rank: c_int, n: *c_int, _in: *mut c_float,
+                          out: *mut c_float, kind: *fftw_r2r_kind,
+                          flags: c_uint) -> fftwf_plan;
+    pub fn fftwf_plan_r2r_1d(n: c_int, _in: *mut c_float, out: *mut c_float,
+                             kind: fftw_r2r_kind, flags: c_uint) ->
+     fftwf_plan;
+    pub fn fftwf_plan_r2r_2d(n0: c_int, n1: c_int, _in: *mut c_float,
+                             out: *mut c_float, kind0: fftw_r2r_kind,
+                             kind1: fftw_r2r_kind, flags: c_uint) ->
+     fftwf_plan;
+    pub fn fftwf_plan_r2r_3d(n0: c_int, n1: c_int, n2: c_int,
+                             _in: *mut c_float, out: *mut c_float,
+                             kind0: fftw_r2r_kind, kind1: fftw_r2r_kind,
+                             kind2: fftw_r2r_kind, flags: c_uint) ->
+     fftwf_plan;
+    pub fn fftwf_plan_many_r2r(rank: c_int, n: *c_int, howmany: c_int,
+                               _in: *mut c_float, inembed: *c_int,
+                               istride: c_int, idist: c_int,
+                               out: *mut c_float, onembed: *c_int,
+                               ostride: c_int, odist: c_int,
+                               kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftwf_plan;
+    pub fn fftwf_plan_guru_dft(rank: c_int, dims: *fftw_iodim,
+                               howmany_rank: c_int, howmany_dims: *fftw_iodim,
+                               _in: *mut fftwf_complex,
+                               out: *mut fftwf_complex, sign: c_int,
+                               flags: c_uint) -> fftwf_plan;
+    pub fn fftwf_plan_guru_split_dft(rank: c_int, dims: *fftw_iodim,
+                                     howmany_rank: c_int,
+                                     howmany_dims: *fftw_iodim,
+                                     ri: *mut c_float, ii: *mut c_float,
+                                     ro: *mut c_float, io: *mut c_float,
+                                     flags: c_uint) -> fftwf_plan;
+    pub fn fftwf_plan_guru_dft_r2c(rank: c_int, dims: *fftw_iodim,
+                                   howmany_rank: c_int,
+                                   howmany_dims: *fftw_iodim,
+                                   _in: *mut c_float, out: *mut fftwf_complex,
+                                   flags: c_uint) -> fftwf_plan;
+    pub fn fftwf_plan_guru_split_dft_r2c(rank: c_int, dims: *fftw_iodim,
+                                         howmany_rank: c_int,
+                                         howmany_dims: *fftw_iodim,
+                                         _in: *mut c_float, ro: *mut c_float,
+                                         io: *mut c_float, flags: c_uint) ->
+     fftwf_plan;
+    pub fn fftwf_plan_guru_dft_c2r(rank: c_int, dims: *fftw_iodim,
+                                   howmany_rank: c_int,
+                                   howmany_dims: *fftw_iodim,
+                                   _in: *mut fftwf_complex, out: *mut c_float,
+                                   flags: c_uint) -> fftwf_plan;
+    pub fn fftwf_plan_guru_split_dft_c2r(rank: c_int, dims: *fftw_iodim,
+                                         howmany_rank: c_int,
+                                         howmany_dims: *fftw_iodim,
+                                         ri: *mut c_float, ii: *mut c_float,
+                                         out: *mut c_float, flags: c_uint) ->
+     fftwf_plan;
+    pub fn fftwf_plan_guru_r2r(rank: c_int, dims: *fftw_iodim,
+                               howmany_rank: c_int, howmany_dims: *fftw_iodim,
+                               _in: *mut c_float, out: *mut c_float,
+                               kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftwf_plan;
+    pub fn fftwf_plan_guru64_dft(rank: c_int, dims: *fftw_iodim64,
+                                 howmany_rank: c_int,
+                                 howmany_dims: *fftw_iodim64,
+                                 _in: *mut fftwf_complex,
+                                 out: *mut fftwf_complex, sign: c_int,
+                                 flags: c_uint) -> fftwf_plan;
+    pub fn fftwf_plan_guru64_split_dft(rank: c_int, dims: *fftw_iodim64,
+                                       howmany_rank: c_int,
+                                       howmany_dims: *fftw_iodim64,
+                                       ri: *mut c_float, ii: *mut c_float,
+                                       ro: *mut c_float, io: *mut c_float,
+                                       flags: c_uint) -> fftwf_plan;
+    pub fn fftwf_plan_guru64_dft_r2c(rank: c_int, dims: *fftw_iodim64,
+                                     howmany_rank: c_int,
+                                     howmany_dims: *fftw_iodim64,
+                                     _in: *mut c_float,
+                                     out: *mut fftwf_complex, flags: c_uint)
+     -> fftwf_plan;
+    pub fn fftwf_plan_guru64_split_dft_r2c(rank: c_int, dims: *fftw_iodim64,
+                                           howmany_rank: c_int,
+                                           howmany_dims: *fftw_iodim64,
+                                           _in: *mut c_float,
+                                           ro: *mut c_float, io: *mut c_float,
+                                           flags: c_uint) -> fftwf_plan;
+    pub fn fftwf_plan_guru64_dft_c2r(rank: c_int, dims: *fftw_iodim64,
+                                     howmany_rank: c_int,
+                                     howmany_dims: *fftw_iodim64,
+                                     _in: *mut fftwf_complex,
+                                     out: *mut c_float, flags: c_uint) ->
+     fftwf_plan;
+    pub fn fftwf_plan_guru64_split_dft_c2r(rank: c_int, dims: *fftw_iodim64,
+                                           howmany_rank: c_int,
+                                           howmany_dims: *fftw_iodim64,
+                                           ri: *mut c_float, ii: *mut c_float,
+                                           out: *mut c_float, flags: c_uint)
+     -> fftwf_plan;
+    pub fn fftwf_plan_guru64_r2r(rank: c_int, dims: *fftw_iodim64,
+                                 howmany_rank: c_int,
+                                 howmany_dims: *fftw_iodim64,
+                                 _in: *mut c_float, out: *mut c_float,
+                                 kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftwf_plan;
+    pub fn fftwf_execute_r2r(p: fftwf_plan, _in: *mut c_float,
+                             out: *mut c_float);
     pub fn fftwf_destroy_plan(p: fftwf_plan);
+    pub fn fftwf_forget_wisdom();
     pub fn fftwf_cleanup();
     pub fn fftwf_set_timelimit(t: c_double);
-    pub fn fftwf_plan_with_nthreads(nthreads: c_int);
-    pub fn fftwf_init_threads() -> c_int;
-    pub fn fftwf_cleanup_threads();
+    pub fn fftwf_export_wisdom_to_filename(filename: *c_char) -> c_int;
+    pub fn fftwf_export_wisdom_to_file(output_file: *mut FILE);
+    pub fn fftwf_export_wisdom_to_string() -> *mut c_char;
+    pub fn fftwf_export_wisdom(write_char: fftwf_write_char_func,
+                               data: *mut c_void);
+    pub fn fftwf_import_system_wisdom() -> c_int;
+    pub fn fftwf_import_wisdom_from_filename(filename: *c_char) -> c_int;
+    pub fn fftwf_import_wisdom_from_file(input_file: *mut FILE) -> c_int;
+    pub fn fftwf_import_wisdom_from_string(input_string: *c_char) -> c_int;
+    pub fn fftwf_import_wisdom(read_char: fftwf_read_char_func,
+                               data: *mut c_void) -> c_int;
     pub fn fftwf_fprint_plan(p: fftwf_plan, output_file: *mut FILE);
     pub fn fftwf_print_plan(p: fftwf_plan);
     pub fn fftwf_malloc(n: size_t) -> *mut c_void;
     pub fn fftwf_alloc_real(n: size_t) -> *mut c_float;
     pub fn fftwf_alloc_complex(n: size_t) -> *mut fftwf_complex;
     pub fn fftwf_free(p: *mut c_void);
+    pub fn fftwf_alignment_of(p: *mut c_float) -> c_int;
     pub fn fftwf_flops(p: fftwf_plan, add: *mut c_double, mul: *mut c_double,
                        fmas: *mut c_double);
     pub fn fftwf_estimate_cost(p: fftwf_plan) -> c_double;
     pub fn fftwf_cost(p: fftwf_plan) -> c_double;
+    pub static fftwf_version: [c_char, ..0u];
+    pub static fftwf_cc: [c_char, ..0u];
+    pub static fftwf_codelet_optim: [c_char, ..0u];
+}
+#[cfg(fftw3_threads, not(fftw3_omp))]
+#[link(name = "fftw3f_threads")]
+extern "C" {
+    pub fn fftwf_plan_with_nthreads(nthreads: c_int);
+    pub fn fftwf_init_threads() -> c_int;
+    pub fn fftwf_cleanup_threads();
+}
+#[cfg(fftw3_threads, fftw3_omp)]
+#[link(name = "fftw3f_omp")]
+extern "C" {
+    pub fn fftwf_plan_with_nthreads(nthreads: c_int);
+    pub fn fftwf_init_threads() -> c_int;
+    pub fn fftwf_cleanup_threads();
+}
+#[cfg(target_arch = "x86_64")]
+#[link(name = "fftw3l")]
+extern "C" {
     pub fn fftwl_execute(p: fftwl_plan);
     pub fn fftwl_plan_dft(rank: c_int, n: *c_int, _in: *mut fftwl_complex,
                           out: *mut fftwl_complex, sign: c_int, flags: c_uint)
@@ -237,67 +557,449 @@ extern "C" {
                                flags: c_uint) -> fftwl_plan;
     pub fn fftwl_execute_dft(p: fftwl_plan, _in: *mut fftwl_complex,
                              out: *mut fftwl_complex);
-    pub fn fftwl_execute_split_dft(p: fftwl_plan, ri: *mut c_double,
-                                   ii: *mut c_double, ro: *mut c_double,
-                                   io: *mut c_double);
+    pub fn fftwl_execute_split_dft(p: fftwl_plan, ri: *mut c_long_double,
+                                   ii: *mut c_long_double,
+                                   ro: *mut c_long_double,
+                                   io: *mut c_long_double);
     pub fn fftwl_plan_many_dft_r2c(rank: c_int, n: *c_int, howmany: c_int,
-                                   _in: *mut c_double, inembed: *c_int,
+                                   _in: *mut c_long_double, inembed: *c_int,
                                    istride: c_int, idist: c_int,
                                    out: *mut fftwl_complex, onembed: *c_int,
                                    ostride: c_int, odist: c_int,
                                    flags: c_uint) -> fftwl_plan;
-    pub fn fftwl_plan_dft_r2c(rank: c_int, n: *c_int, _in: *mut c_double,
+    pub fn fftwl_plan_dft_r2c(rank: c_int, n: *c_int, _in: *mut c_long_double,
                               out: *mut fftwl_complex, flags: c_uint) ->
      fftwl_plan;
-    pub fn fftwl_plan_dft_r2c_1d(n: c_int, _in: *mut c_double,
+    pub fn fftwl_plan_dft_r2c_1d(n: c_int, _in: *mut c_long_double,
                                  out: *mut fftwl_complex, flags: c_uint) ->
      fftwl_plan;
-    pub fn fftwl_plan_dft_r2c_2d(n0: c_int, n1: c_int, _in: *mut c_double,
+    pub fn fftwl_plan_dft_r2c_2d(n0: c_int, n1: c_int,
+                                 _in: *mut c_long_double,
                                  out: *mut fftwl_complex, flags: c_uint) ->
      fftwl_plan;
     pub fn fftwl_plan_dft_r2c_3d(n0: c_int, n1: c_int, n2: c_int,
-                                 _in: *mut c_double, out: *mut fftwl_complex,
-                                 flags: c_uint) -> fftwl_plan;
+                                 _in: *mut c_long_double,
+                                 out: *mut fftwl_complex, flags: c_uint) ->
+     fftwl_plan;
     pub fn fftwl_plan_many_dft_c2r(rank: c_int, n: *c_int, howmany: c_int,
                                    _in: *mut fftwl_complex, inembed: *c_int,
                                    istride: c_int, idist: c_int,
-                                   out: *mut c_double, onembed: *c_int,
+                                   out: *mut c_long_double, onembed: *c_int,
                                    ostride: c_int, odist: c_int,
                                    flags: c_uint) -> fftwl_plan;
     pub fn fftwl_plan_dft_c2r(rank: c_int, n: *c_int, _in: *mut fftwl_complex,
-                              out: *mut c_double, flags: c_uint) ->
+                              out: *mut c_long_double, flags: c_uint) ->
      fftwl_plan;
     pub fn fftwl_plan_dft_c2r_1d(n: c_int, _in: *mut fftwl_complex,
-                                 out: *mut c_double, flags: c_uint) ->
+                                 out: *mut c_long_double, flags: c_uint) ->
      fftwl_plan;
     pub fn fftwl_plan_dft_c2r_2d(n0: c_int, n1: c_int,
-                                 _in: *mut fftwl_complex, out: *mut c_double,
-                                 flags: c_uint) -> fftwl_plan;
+                                 _in: *mut fftwl_complex,
+                                 out: *mut c_long_double, flags: c_uint) ->
+     fftwl_plan;
     pub fn fftwl_plan_dft_c2r_3d(n0: c_int, n1: c_int, n2: c_int,
-                                 _in: *mut fftwl_complex, out: *mut c_double,
-                                 flags: c_uint) -> fftwl_plan;
-    pub fn fftwl_execute_dft_r2c(p: fftwl_plan, _in: *mut c_double,
+                                 _in: *mut fftwl_complex,
+                                 out: *mut c_long_double, flags: c_uint) ->
+     fftwl_plan;
+    pub fn fftwl_execute_dft_r2c(p: fftwl_plan, _in: *mut c_long_double,
                                  out: *mut fftwl_complex);
     pub fn fftwl_execute_dft_c2r(p: fftwl_plan, _in: *mut fftwl_complex,
-                                 out: *mut c_double);
-    pub fn fftwl_execute_split_dft_r2c(p: fftwl_plan, _in: *mut c_double,
-                                       ro: *mut c_double, io: *mut c_double);
-    pub fn fftwl_execute_split_dft_c2r(p: fftwl_plan, ri: *mut c_double,
-                                       ii: *mut c_double, out: *mut c_double);
+                                 out: *mut c_long_double);
+    pub fn fftwl_execute_split_dft_r2c(p: fftwl_plan, _in: *mut c_long_double,
+                                       ro: *mut c_long_double,
+                                       io: *mut c_long_double);
+    pub fn fftwl_execute_split_dft_c2r(p: fftwl_plan, ri: *mut c_long_double,
+                                       ii: *mut c_long_double,
+                                       out: *mut c_long_double);
+    pub fn fftwl_plan_r2r(rank: c_int, n: *c_int, _in: *mut c_long_double,
+                          out: *mut c_long_double, kind: *fftw_r2r_kind,
+                          flags: c_uint) -> fftwl_plan;
+    pub fn fftwl_plan_r2r_1d(n: c_int, _in: *mut c_long_double,
+                             out: *mut c_long_double, kind: fftw_r2r_kind,
+                             flags: c_uint) -> fftwl_plan;
+    pub fn fftwl_plan_r2r_2d(n0: c_int, n1: c_int, _in: *mut c_long_double,
+                             out: *mut c_long_double, kind0: fftw_r2r_kind,
+                             kind1: fftw_r2r_kind, flags: c_uint) ->
+     fftwl_plan;
+    pub fn fftwl_plan_r2r_3d(n0: c_int, n1: c_int, n2: c_int,
+                             _in: *mut c_long_double, out: *mut c_long_double,
+                             kind0: fftw_r2r_kind, kind1: fftw_r2r_kind,
+                             kind2: fftw_r2r_kind, flags: c_uint) ->
+     fftwl_plan;
+    pub fn fftwl_plan_many_r2r(rank: c_int, n: *c_int, howmany: c_int,
+                               _in: *mut c_long_double, inembed: *c_int,
+                               istride: c_int, idist: c_int,
+                               out: *mut c_long_double, onembed: *c_int,
+                               ostride: c_int, odist: c_int,
+                               kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftwl_plan;
+    pub fn fftwl_plan_guru_dft(rank: c_int, dims: *fftw_iodim,
+                               howmany_rank: c_int, howmany_dims: *fftw_iodim,
+                               _in: *mut fftwl_complex,
+                               out: *mut fftwl_complex, sign: c_int,
+                               flags: c_uint) -> fftwl_plan;
+    pub fn fftwl_plan_guru_split_dft(rank: c_int, dims: *fftw_iodim,
+                                     howmany_rank: c_int,
+                                     howmany_dims: *fftw_iodim,
+                                     ri: *mut c_long_double,
+                                     ii: *mut c_long_double,
+                                     ro: *mut c_long_double,
+                                     io: *mut c_long_double, flags: c_uint) ->
+     fftwl_plan;
+    pub fn fftwl_plan_guru_dft_r2c(rank: c_int, dims: *fftw_iodim,
+                                   howmany_rank: c_int,
+                                   howmany_dims: *fftw_iodim,
+                                   _in: *mut c_long_double,
+                                   out: *mut fftwl_complex, flags: c_uint) ->
+     fftwl_plan;
+    pub fn fftwl_plan_guru_split_dft_r2c(rank: c_int, dims: *fftw_iodim,
+                                         howmany_rank: c_int,
+                                         howmany_dims: *fftw_iodim,
+                                         _in: *mut c_long_double,
+                                         ro: *mut c_long_double,
+                                         io: *mut c_long_double,
+                                         flags: c_uint) -> fftwl_plan;
+    pub fn fftwl_plan_guru_dft_c2r(rank: c_int, dims: *fftw_iodim,
+                                   howmany_rank: c_int,
+                                   howmany_dims: *fftw_iodim,
+                                   _in: *mut fftwl_complex,
+                                   out: *mut c_long_double, flags: c_uint) ->
+     fftwl_plan;
+    pub fn fftwl_plan_guru_split_dft_c2r(rank: c_int, dims: *fftw_iodim,
+                                         howmany_rank: c_int,
+                                         howmany_dims: *fftw_iodim,
+                                         ri: *mut c_long_double,
+                                         ii: *mut c_long_double,
+                                         out: *mut c_long_double,
+                                         flags: c_uint) -> fftwl_plan;
+    pub fn fftwl_plan_guru_r2r(rank: c_int, dims: *fftw_iodim,
+                               howmany_rank: c_int, howmany_dims: *fftw_iodim,
+                               _in: *mut c_long_double,
+                               out: *mut c_long_double, kind: *fftw_r2r_kind,
+                               flags: c_uint) -> fftwl_plan;
+    pub fn fftwl_plan_guru64_dft(rank: c_int, dims: *fftw_iodim64,
+                                 howmany_rank: c_int,
+                                 howmany_dims: *fftw_iodim64,
+                                 _in: *mut fftwl_complex,
+                                 out: *mut fftwl_complex, sign: c_int,
+                                 flags: c_uint) -> fftwl_plan;
+    pub fn fftwl_plan_guru64_split_dft(rank: c_int, dims: *fftw_iodim64,
+                                       howmany_rank: c_int,
+                                       howmany_dims: *fftw_iodim64,
+                                       ri: *mut c_long_double,
+                                       ii: *mut c_long_double,
+                                       ro: *mut c_long_double,
+                                       io: *mut c_long_double, flags: c_uint)
+     -> fftwl_plan;
+    pub fn fftwl_plan_guru64_dft_r2c(rank: c_int, dims: *fftw_iodim64,
+                                     howmany_rank: c_int,
+                                     howmany_dims: *fftw_iodim64,
+                                     _in: *mut c_long_double,
+                                     out: *mut fftwl_complex, flags: c_uint)
+     -> fftwl_plan;
+    pub fn fftwl_plan_guru64_split_dft_r2c(rank: c_int, dims: *fftw_iodim64,
+                                           howmany_rank: c_int,
+                                           howmany_dims: *fftw_iodim64,
+                                           _in: *mut c_long_double,
+                                           ro: *mut c_long_double,
+                                           io: *mut c_long_double,
+                                           flags: c_uint) -> fftwl_plan;
+    pub fn fftwl_plan_guru64_dft_c2r(rank: c_int, dims: *fftw_iodim64,
+                                     howmany_rank: c_int,
+                                     howmany_dims: *fftw_iodim64,
+                                     _in: *mut fftwl_complex,
+                                     out: *mut c_long_double, flags: c_uint)
+     -> fftwl_plan;
+    pub fn fftwl_plan_guru64_split_dft_c2r(rank: c_int, dims: *fftw_iodim64,
+                                           howmany_rank: c_int,
+                                           howmany_dims: *fftw_iodim64,
+                                           ri: *mut c_long_double,
+                                           ii: *mut c_long_double,
+                                           out: *mut c_long_double,
+                                           flags: c_uint) -> fftwl_plan;
+    pub fn fftwl_plan_guru64_r2r(rank: c_int, dims: *fftw_iodim64,
+                                 howmany_rank: c_int,
+                                 howmany_dims: *fftw_iodim64,
+                                 _in: *mut c_long_double,
+                                 out: *mut c_long_double,
+                                 kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftwl_plan;
+    pub fn fftwl_execute_r2r(p: fftwl_plan, _in: *mut c_long_double,
+                             out: *mut c_long_double);
     pub fn fftwl_destroy_plan(p: fftwl_plan);
+    pub fn fftwl_forget_wisdom();
     pub fn fftwl_cleanup();
     pub fn fftwl_set_timelimit(t: c_double);
-    pub fn fftwl_plan_with_nthreads(nthreads: c_int);
-    pub fn fftwl_init_threads() -> c_int;
-    pub fn fftwl_cleanup_threads();
+    pub fn fftwl_export_wisdom_to_filename(filename: *c_char) -> c_int;
+    pub fn fftwl_export_wisdom_to_file(output_file: *mut FILE);
+    pub fn fftwl_export_wisdom_to_string() -> *mut c_char;
+    pub fn fftwl_export_wisdom(write_char: fftwl_write_char_func,
+                               data: *mut c_void);
+    pub fn fftwl_import_system_wisdom() -> c_int;
+    pub fn fftwl_import_wisdom_from_filename(filename: *c_char) -> c_int;
+    pub fn fftwl_import_wisdom_from_file(input_file: *mut FILE) -> c_int;
+    pub fn fftwl_import_wisdom_from_string(input_string: *c_char) -> c_int;
+    pub fn fftwl_import_wisdom(read_char: fftwl_read_char_func,
+                               data: *mut c_void) -> c_int;
     pub fn fftwl_fprint_plan(p: fftwl_plan, output_file: *mut FILE);
     pub fn fftwl_print_plan(p: fftwl_plan);
     pub fn fftwl_malloc(n: size_t) -> *mut c_void;
-    pub fn fftwl_alloc_real(n: size_t) -> *mut c_double;
+    pub fn fftwl_alloc_real(n: size_t) -> *mut c_long_double;
     pub fn fftwl_alloc_complex(n: size_t) -> *mut fftwl_complex;
     pub fn fftwl_free(p: *mut c_void);
+    pub fn fftwl_alignment_of(p: *mut c_long_double) -> c_int;
     pub fn fftwl_flops(p: fftwl_plan, add: *mut c_double, mul: *mut c_double,
                        fmas: *mut c_double);
     pub fn fftwl_estimate_cost(p: fftwl_plan) -> c_double;
     pub fn fftwl_cost(p: fftwl_plan) -> c_double;
+    pub static fftwl_version: [c_char, ..0u];
+    pub static fftwl_cc: [c_char, ..0u];
+    pub static fftwl_codelet_optim: [c_char, ..0u];
+}
+#[cfg(target_arch = "x86_64", fftw3_threads, not(fftw3_omp))]
+#[link(name = "fftw3l_threads")]
+extern "C" {
+    pub fn fftwl_plan_with_nthreads(nthreads: c_int);
+    pub fn fftwl_init_threads() -> c_int;
+    pub fn fftwl_cleanup_threads();
+}
+#[cfg(target_arch = "x86_64", fftw3_threads, fftw3_omp)]
+#[link(name = "fftw3l_omp")]
+extern "C" {
+    pub fn fftwl_plan_with_nthreads(nthreads: c_int);
+    pub fn fftwl_init_threads() -> c_int;
+    pub fn fftwl_cleanup_threads();
+}
+#[cfg(fftw3q)]
+#[link(name = "fftw3q")]
+#[link(name = "quadmath")]
+extern "C" {
+    pub fn fftwq_execute(p: fftwq_plan);
+    pub fn fftwq_plan_dft(rank: c_int, n: *c_int, _in: *mut fftwq_complex,
+                          out: *mut fftwq_complex, sign: c_int, flags: c_uint)
+     -> fftwq_plan;
+    pub fn fftwq_plan_dft_1d(n: c_int, _in: *mut fftwq_complex,
+                             out: *mut fftwq_complex, sign: c_int,
+                             flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_dft_2d(n0: c_int, n1: c_int, _in: *mut fftwq_complex,
+                             out: *mut fftwq_complex, sign: c_int,
+                             flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_dft_3d(n0: c_int, n1: c_int, n2: c_int,
+                             _in: *mut fftwq_complex, out: *mut fftwq_complex,
+                             sign: c_int, flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_many_dft(rank: c_int, n: *c_int, howmany: c_int,
+                               _in: *mut fftwq_complex, inembed: *c_int,
+                               istride: c_int, idist: c_int,
+                               out: *mut fftwq_complex, onembed: *c_int,
+                               ostride: c_int, odist: c_int, sign: c_int,
+                               flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_execute_dft(p: fftwq_plan, _in: *mut fftwq_complex,
+                             out: *mut fftwq_complex);
+    pub fn fftwq_execute_split_dft(p: fftwq_plan, ri: *mut __float128,
+                                   ii: *mut __float128, ro: *mut __float128,
+                                   io: *mut __float128);
+    pub fn fftwq_plan_many_dft_r2c(rank: c_int, n: *c_int, howmany: c_int,
+                                   _in: *mut __float128, inembed: *c_int,
+                                   istride: c_int, idist: c_int,
+                                   out: *mut fftwq_complex, onembed: *c_int,
+                                   ostride: c_int, odist: c_int,
+                                   flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_dft_r2c(rank: c_int, n: *c_int, _in: *mut __float128,
+                              out: *mut fftwq_complex, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_dft_r2c_1d(n: c_int, _in: *mut __float128,
+                                 out: *mut fftwq_complex, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_dft_r2c_2d(n0: c_int, n1: c_int, _in: *mut __float128,
+                                 out: *mut fftwq_complex, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_dft_r2c_3d(n0: c_int, n1: c_int, n2: c_int,
+                                 _in: *mut __float128,
+                                 out: *mut fftwq_complex, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_many_dft_c2r(rank: c_int, n: *c_int, howmany: c_int,
+                                   _in: *mut fftwq_complex, inembed: *c_int,
+                                   istride: c_int, idist: c_int,
+                                   out: *mut __float128, onembed: *c_int,
+                                   ostride: c_int, odist: c_int,
+                                   flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_dft_c2r(rank: c_int, n: *c_int, _in: *mut fftwq_complex,
+                              out: *mut __float128, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_dft_c2r_1d(n: c_int, _in: *mut fftwq_complex,
+                                 out: *mut __float128, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_dft_c2r_2d(n0: c_int, n1: c_int,
+                                 _in: *mut fftwq_complex,
+                                 out: *mut __float128, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_dft_c2r_3d(n0: c_int, n1: c_int, n2: c_int,
+                                 _in: *mut fftwq_complex,
+                                 out: *mut __float128, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_execute_dft_r2c(p: fftwq_plan, _in: *mut __float128,
+                                 out: *mut fftwq_complex);
+    pub fn fftwq_execute_dft_c2r(p: fftwq_plan, _in: *mut fftwq_complex,
+                                 out: *mut __float128);
+    pub fn fftwq_execute_split_dft_r2c(p: fftwq_plan, _in: *mut __float128,
+                                       ro: *mut __float128,
+                                       io: *mut __float128);
+    pub fn fftwq_execute_split_dft_c2r(p: fftwq_plan, ri: *mut __float128,
+                                       ii: *mut __float128,
+                                       out: *mut __float128);
+    pub fn fftwq_plan_r2r(rank: c_int, n: *c_int, _in: *mut __float128,
+                          out: *mut __float128, kind: *fftw_r2r_kind,
+                          flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_r2r_1d(n: c_int, _in: *mut __float128,
+                             out: *mut __float128, kind: fftw_r2r_kind,
+                             flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_r2r_2d(n0: c_int, n1: c_int, _in: *mut __float128,
+                             out: *mut __float128, kind0: fftw_r2r_kind,
+                             kind1: fftw_r2r_kind, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_r2r_3d(n0: c_int, n1: c_int, n2: c_int,
+                             _in: *mut __float128, out: *mut __float128,
+                             kind0: fftw_r2r_kind, kind1: fftw_r2r_kind,
+                             kind2: fftw_r2r_kind, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_many_r2r(rank: c_int, n: *c_int, howmany: c_int,
+                               _in: *mut __float128, inembed: *c_int,
+                               istride: c_int, idist: c_int,
+                               out: *mut __float128, onembed: *c_int,
+                               ostride: c_int, odist: c_int,
+                               kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_guru_dft(rank: c_int, dims: *fftw_iodim,
+                               howmany_rank: c_int, howmany_dims: *fftw_iodim,
+                               _in: *mut fftwq_complex,
+                               out: *mut fftwq_complex, sign: c_int,
+                               flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_guru_split_dft(rank: c_int, dims: *fftw_iodim,
+                                     howmany_rank: c_int,
+                                     howmany_dims: *fftw_iodim,
+                                     ri: *mut __float128, ii: *mut __float128,
+                                     ro: *mut __float128, io: *mut __float128,
+                                     flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_guru_dft_r2c(rank: c_int, dims: *fftw_iodim,
+                                   howmany_rank: c_int,
+                                   howmany_dims: *fftw_iodim,
+                                   _in: *mut __float128,
+                                   out: *mut fftwq_complex, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_guru_split_dft_r2c(rank: c_int, dims: *fftw_iodim,
+                                         howmany_rank: c_int,
+                                         howmany_dims: *fftw_iodim,
+                                         _in: *mut __float128,
+                                         ro: *mut __float128,
+                                         io: *mut __float128, flags: c_uint)
+     -> fftwq_plan;
+    pub fn fftwq_plan_guru_dft_c2r(rank: c_int, dims: *fftw_iodim,
+                                   howmany_rank: c_int,
+                                   howmany_dims: *fftw_iodim,
+                                   _in: *mut fftwq_complex,
+                                   out: *mut __float128, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_guru_split_dft_c2r(rank: c_int, dims: *fftw_iodim,
+                                         howmany_rank: c_int,
+                                         howmany_dims: *fftw_iodim,
+                                         ri: *mut __float128,
+                                         ii: *mut __float128,
+                                         out: *mut __float128, flags: c_uint)
+     -> fftwq_plan;
+    pub fn fftwq_plan_guru_r2r(rank: c_int, dims: *fftw_iodim,
+                               howmany_rank: c_int, howmany_dims: *fftw_iodim,
+                               _in: *mut __float128, out: *mut __float128,
+                               kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_guru64_dft(rank: c_int, dims: *fftw_iodim64,
+                                 howmany_rank: c_int,
+                                 howmany_dims: *fftw_iodim64,
+                                 _in: *mut fftwq_complex,
+                                 out: *mut fftwq_complex, sign: c_int,
+                                 flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_guru64_split_dft(rank: c_int, dims: *fftw_iodim64,
+                                       howmany_rank: c_int,
+                                       howmany_dims: *fftw_iodim64,
+                                       ri: *mut __float128,
+                                       ii: *mut __float128,
+                                       ro: *mut __float128,
+                                       io: *mut __float128, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_guru64_dft_r2c(rank: c_int, dims: *fftw_iodim64,
+                                     howmany_rank: c_int,
+                                     howmany_dims: *fftw_iodim64,
+                                     _in: *mut __float128,
+                                     out: *mut fftwq_complex, flags: c_uint)
+     -> fftwq_plan;
+    pub fn fftwq_plan_guru64_split_dft_r2c(rank: c_int, dims: *fftw_iodim64,
+                                           howmany_rank: c_int,
+                                           howmany_dims: *fftw_iodim64,
+                                           _in: *mut __float128,
+                                           ro: *mut __float128,
+                                           io: *mut __float128, flags: c_uint)
+     -> fftwq_plan;
+    pub fn fftwq_plan_guru64_dft_c2r(rank: c_int, dims: *fftw_iodim64,
+                                     howmany_rank: c_int,
+                                     howmany_dims: *fftw_iodim64,
+                                     _in: *mut fftwq_complex,
+                                     out: *mut __float128, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_plan_guru64_split_dft_c2r(rank: c_int, dims: *fftw_iodim64,
+                                           howmany_rank: c_int,
+                                           howmany_dims: *fftw_iodim64,
+                                           ri: *mut __float128,
+                                           ii: *mut __float128,
+                                           out: *mut __float128,
+                                           flags: c_uint) -> fftwq_plan;
+    pub fn fftwq_plan_guru64_r2r(rank: c_int, dims: *fftw_iodim64,
+                                 howmany_rank: c_int,
+                                 howmany_dims: *fftw_iodim64,
+                                 _in: *mut __float128, out: *mut __float128,
+                                 kind: *fftw_r2r_kind, flags: c_uint) ->
+     fftwq_plan;
+    pub fn fftwq_execute_r2r(p: fftwq_plan, _in: *mut __float128,
+                             out: *mut __float128);
+    pub fn fftwq_destroy_plan(p: fftwq_plan);
+    pub fn fftwq_forget_wisdom();
+    pub fn fftwq_cleanup();
+    pub fn fftwq_set_timelimit(t: c_double);
+    pub fn fftwq_export_wisdom_to_filename(filename: *c_char) -> c_int;
+    pub fn fftwq_export_wisdom_to_file(output_file: *mut FILE);
+    pub fn fftwq_export_wisdom_to_string() -> *mut c_char;
+    pub fn fftwq_export_wisdom(write_char: fftwq_write_char_func,
+                               data: *mut c_void);
+    pub fn fftwq_import_system_wisdom() -> c_int;
+    pub fn fftwq_import_wisdom_from_filename(filename: *c_char) -> c_int;
+    pub fn fftwq_import_wisdom_from_file(input_file: *mut FILE) -> c_int;
+    pub fn fftwq_import_wisdom_from_string(input_string: *c_char) -> c_int;
+    pub fn fftwq_import_wisdom(read_char: fftwq_read_char_func,
+                               data: *mut c_void) -> c_int;
+    pub fn fftwq_fprint_plan(p: fftwq_plan, output_file: *mut FILE);
+    pub fn fftwq_print_plan(p: fftwq_plan);
+    pub fn fftwq_malloc(n: size_t) -> *mut c_void;
+    pub fn fftwq_alloc_real(n: size_t) -> *mut __float128;
+    pub fn fftwq_alloc_complex(n: size_t) -> *mut fftwq_complex;
+    pub fn fftwq_free(p: *mut c_void);
+    pub fn fftwq_alignment_of(p: *mut __float128) -> c_int;
+    pub fn fftwq_flops(p: fftwq_plan, add: *mut c_double, mul: *mut c_double,
+                       fmas: *mut c_double);
+    pub fn fftwq_estimate_cost(p: fftwq_plan) -> c_double;
+    pub fn fftwq_cost(p: fftwq_plan) -> c_double;
+    pub static fftwq_version: [c_char, ..0u];
+    pub static fftwq_cc: [c_char, ..0u];
+    pub static fftwq_codelet_optim: [c_char, ..0u];
+}
+#[cfg(fftw3q, fftw3_threads, not(fftw3_omp))]
+#[link(name = "fftw3q_threads")]
+extern "C" {
+    pub fn fftwq_plan_with_nthreads(nthreads: c_int);
+    pub fn fftwq_init_threads() -> c_int;
+    pub fn fftwq_cleanup_threads();
+}
+#[cfg(fftw3q, fftw3_threads, fftw3_omp)]
+#[link(name = "fftw3q_omp")]
+extern "C" {
+    pub fn fftwq_plan_with_nthreads(nthreads: c_int);
+    pub fn fftwq_init_threads() -> c_int;
+    pub fn fftwq_cleanup_threads();
 }
@@ -0,0 +1,224 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Plan cache : remembers on disk the transforms planned with more rigor than `Estimate`.
+//!
+//! Wisdom makes planning a transform again almost free, but only while the planner finds
+//! it : a `PlanCache` records the precision, kind, shape, layout, planner flags and
+//! number of threads of every transform planned through it, along with the wisdom of
+//! each precision. A recorded transform is then planned from the wisdom only, with
+//! `FFTW_WISDOM_ONLY`, and planned normally again if the wisdom does not hold it.
+//!
+//! The cache directory can be shared by several processes, its files are only used under
+//! a lock on the directory. It is emptied when the version of fftw3 changes, the wisdom of
+//! another version being useless.
+//!
+//! ```rust
+//! use fftw3_rust::{Fftw, PlanCache, Planner, Patient};
+//!
+//! let cache = PlanCache::open(&std::os::tmpdir().join("fftw3_plans")).unwrap();
+//! // Slow the first time the program runs, fast every next time.
+//! let mut fftw = Fftw::from_slice_with([1f64, 0f64, 2f64, 4f64],
+//!                                      Planner::new(Patient).with_cache(cache)).unwrap();
+//! fftw.compute();
+//! ```
+
+use fftw3_bindgen::FFTW_WISDOM_ONLY;
+use fftw3_planner::{Planner, Estimate, prepare};
+use fftw3_wisdom::{Wisdom, wisdom_of, available};
+
+use std::c_str::ToCStr;
+use std::io;
+use std::io::File;
+use std::io::fs;
+use std::libc::{c_int, c_uint, open, close, O_RDWR, O_CREAT, S_IRUSR, S_IWUSR};
+
+use super::{FftwError, CacheDir, Plan, Precision, LOCK, check_plan};
+
+static VERSION_FILE: &'static str = "version";
+static PLANS_FILE: &'static str = "plans";
+static LOCK_FILE: &'static str = "lock";
+
+static LOCK_SH: c_int = 1;
+static LOCK_EX: c_int = 2;
+
+extern {
+  fn flock(fd: c_int, operation: c_int) -> c_int;
+}
+
+/// A directory recording the transforms planned with more rigor than `Estimate`, and the
+/// wisdom needed to plan them again.
+#[deriving(Eq, Clone)]
+pub struct PlanCache {
+  priv dir: Path,
+}
+
+impl PlanCache {
+  /// Opens the cache stored in 'dir', creating the directory if needed, and imports its
+  /// wisdom. The cache is emptied if it was filled with another version of fftw3.
+  /// Returns an error if the directory can not be created or written.
+  pub fn open(dir: &Path) -> Result<PlanCache, FftwError> {
+    let cache = PlanCache { dir: dir.clone() };
+    if !dir.is_dir() && fs::mkdir_recursive(dir, io::UserRWX).is_err() {
+      return Err(CacheDir(dir.clone()));
+    }
+    let _g = match DirLock::new(dir, LOCK_EX) {
+      Some(lock) => lock,
+      None => return Err(CacheDir(dir.clone())),
+    };
+    let version = versions();
+    let version_file = dir.join(VERSION_FILE);
+    match File::open(&version_file).and_then(|mut file| file.read_to_str()) {
+      Ok(ref found) if *found == version => {
+        for wisdom in available().iter() {
+          let path = cache.wisdom_file(wisdom);
+          if path.exists() {
+            // Unreadable wisdom only makes the recorded transforms be planned again.
+            let _ = wisdom.import_from_file(&path);
+          }
+        }
+      }
+      _ => {
+        for wisdom in available().iter() {
+          let _ = fs::unlink(&cache.wisdom_file(wisdom));
+        }
+        let _ = fs::unlink(&dir.join(PLANS_FILE));
+        if File::create(&version_file).and_then(|mut file| file.write_str(version)).is_err() {
+          return Err(CacheDir(dir.clone()));
+        }
+      }
+    }
+    Ok(cache)
+  }
+
+  /// Returns the directory of the cache.
+  pub fn dir<'a>(&'a self) -> &'a Path {
+    &self.dir
+  }
+
+  /// Returns true if the transform 'key' was recorded.
+  fn contains(&self, key: &str) -> bool {
+    match DirLock::new(&self.dir, LOCK_SH) {
+      Some(_g) => self.recorded(key),
+      None => false,
+    }
+  }
+
+  /// Records the transform 'key' and saves the wisdom of 'precision', merged with the
+  /// one other processes saved in the cache. Failures are ignored : the transform is only
+  /// planned normally the next time.
+  fn record(&self, key: &str, precision: Precision) {
+    let _g = match DirLock::new(&self.dir, LOCK_EX) {
+      Some(lock) => lock,
+      None => return,
+    };
+    let wisdom = wisdom_of(precision);
+    let path = self.wisdom_file(&wisdom);
+    if path.exists() {
+      let _ = wisdom.import_from_file(&path);
+    }
+    if wisdom.export_to_file(&path).is_err() || self.recorded(key) {
+      return;
+    }
+    let plans = self.dir.join(PLANS_FILE);
+    let _ = File::open_mode(&plans, io::Append, io::Write).and_then(|mut file| {
+      file.write_line(key)
+    });
+  }
+
+  /// Returns true if the transform 'key' was recorded. The caller holds the lock on the
+  /// directory.
+  fn recorded(&self, key: &str) -> bool {
+    match File::open(&self.dir.join(PLANS_FILE)).and_then(|mut file| file.read_to_str()) {
+      Ok(plans) => plans.lines().any(|line| line == key),
+      Err(_) => false,
+    }
+  }
+
+  /// Returns the path of the file holding the wisdom of 'wisdom' in the cache.
+  fn wisdom_file(&self, wisdom: &Wisdom) -> Path {
+    self.dir.join(format!("wisdom.{}", wisdom))
+  }
+}
+
+/// Lock on a cache directory, shared between processes and released when dropped.
+struct DirLock {
+  fd: c_int,
+}
+
+impl DirLock {
+  /// Takes the lock with the flock operation 'operation', waiting for the other
+  /// processes to release it. Returns None if the lock file can not be opened.
+  fn new(dir: &Path, operation: c_int) -> Option<DirLock> {
+    let fd = dir.join(LOCK_FILE).with_c_str(|path| unsafe {
+      open(path, O_RDWR | O_CREAT, S_IRUSR | S_IWUSR)
+    });
+    if fd < 0 {
+      return None;
+    }
+    if unsafe { flock(fd, operation) } != 0 {
+      unsafe { close(fd); }
+      return None;
+    }
+    Some(DirLock { fd: fd })
+  }
+}
+
+impl Drop for DirLock {
+  fn drop(&mut self) {
+    unsafe { close(self.fd); }
+  }
+}
+
+/// Returns the versions of the libraries of every precision, one per line.
+fn versions() -> ~str {
+  let lines: ~[~str] = available().iter().map(|wisdom| {
+    format!("{} {}", wisdom, wisdom.library_version())
+  }).collect();
+  lines.connect("\n")
+}
+
+/// Returns the description of a transform of the given kind and shape, identifying it
+/// in a cache with the precision and the flags of the planner.
+pub fn describe(kind: &str, shape: &[uint], in_place: bool) -> ~str {
+  let dims: ~[~str] = shape.iter().map(|n| n.to_str()).collect();
+  format!("{} {} {}", kind, dims.connect("x"), if in_place { "in-place" } else { "out" })
+}
+
+/// Same as `describe`, for a transform whose values are laid out in its buffers as told
+/// by 'layout' : the kinds of a real to real transform, the strides of a batch...
+pub fn describe_layout(kind: &str, shape: &[uint], layout: &str, in_place: bool) -> ~str {
+  format!("{} ({})", describe(kind, shape, in_place), layout)
+}
+
+/// Makes a plan with 'make', which calls the planner of 'precision' with the given flags
+/// for the transform 'transform', as returned by `describe`. If the cache of 'planner'
+/// recorded the transform, it is planned from the wisdom only, and planned normally if
+/// the wisdom does not hold it. Takes the lock around each planner call.
+/// Returns `NoPlan` if fftw3 has no plan satisfying the options of the planner.
+pub fn plan_cached(planner: &Planner, precision: Precision, transform: &str,
+                   make: |c_uint| -> Option<Plan>) -> Result<Plan, FftwError> {
+  let cache = match planner.cache() {
+    Some(cache) if planner.rigor() != Estimate => cache,
+    _ => return call_planner(planner, 0, |flags| make(flags)),
+  };
+  let key = format!("{} {} {} {}", precision, transform, planner.fftw_flags(),
+                    planner.threads());
+  if cache.contains(key) {
+    match call_planner(planner, FFTW_WISDOM_ONLY, |flags| make(flags)) {
+      Ok(plan) => return Ok(plan),
+      Err(_) => (),
+    }
+  }
+  let plan = if_ok!(call_planner(planner, 0, |flags| make(flags)));
+  cache.record(key, precision);
+  Ok(plan)
+}
+
+/// Calls 'make' with the flags of 'planner' and the 'extra' ones, under the lock.
+fn call_planner(planner: &Planner, extra: c_uint,
+                make: |c_uint| -> Option<Plan>) -> Result<Plan, FftwError> {
+  check_plan(unsafe {
+    let _g = LOCK.lock();
+    let flags = prepare(planner);
+    make(flags | extra)
+  })
+}
@@ -0,0 +1,36 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Conversions between f64 and the wider floating point types of `LongDouble` and
+//! `Quad`, which Rust has no arithmetic on.
+
+/// Mask of the fraction bits of a f64.
+pub static FRAC_MASK: u64 = (1 << 52) - 1;
+/// Bits of the positive infinite f64.
+pub static F64_INFINITY: u64 = 0x7ff << 52;
+
+/// Returns the bits of the positive f64 closest to m/2^63 * 2^k (ties to even), where
+/// the highest bit of 'm' is set. 'sticky' tells whether the exact value has non-zero
+/// bits below the ones of 'm'. This converts the wider floating point types to f64.
+pub fn round_to_f64(k: int, m: u64, sticky: bool) -> u64 {
+  if k > 1023 {
+    return F64_INFINITY;
+  }
+  // Number of low bits of the mantissa that do not fit in the f64.
+  let shift = if k >= -1022 { 11 } else { 11 + (-1022 - k) as uint };
+  if shift > 64 {
+    return 0;
+  }
+  let kept = if shift == 64 { 0 } else { m >> shift };
+  let rest = m - if shift == 64 { 0 } else { kept << shift };
+  let half = 1u64 << (shift - 1);
+  let up = rest > half || (rest == half && (sticky || kept & 1 == 1));
+  let rounded = if up { kept + 1 } else { kept };
+
+  if k < -1022 {
+    // Subnormal : rounding up to 2^52 gives the bits of the smallest normal value.
+    rounded
+  } else if rounded == 1 << 53 {
+    if k == 1023 { F64_INFINITY } else { ((k + 1024) as u64) << 52 }
+  } else {
+    ((k + 1023) as u64) << 52 | rounded & FRAC_MASK
+  }
+}
@@ -0,0 +1,263 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Real scalar types of the transforms, each computed by its own version of fftw3.
+//!
+//! `FftwFloat` gives the planners of the fftw*_ family matching a scalar type, so the
+//! constructors of `Fftw` are written once for every precision : f64 and f32, and the
+//! wider `LongDouble` and `Quad` when they are available.
+//!
+//! ```rust
+//! use fftw3_rust::{Fftw, FftwFloat};
+//! use num::complex::Cmplx;
+//!
+//! fn spectrum<F: FftwFloat>(input: &[Cmplx<F>]) -> ~[Cmplx<F>] {
+//!   let mut fftw = Fftw::from_slice_nd([input.len()], input).unwrap();
+//!   fftw.compute().unwrap().to_owned()
+//! }
+//! ```
+
+use num::complex::Cmplx;
+
+use fftw3_bindgen::{fftw_iodim64, fftw_r2r_kind,
+                    fftw_alloc_complex, fftw_plan_dft, fftw_plan_dft_r2c, fftw_plan_dft_c2r,
+                    fftw_plan_many_dft, fftw_plan_many_dft_r2c, fftw_plan_many_dft_c2r,
+                    fftw_plan_r2r, fftw_plan_guru64_dft, fftw_plan_guru64_dft_r2c,
+                    fftw_plan_guru64_dft_c2r, fftw_plan_guru64_r2r,
+                    fftwf_alloc_complex, fftwf_plan_dft, fftwf_plan_dft_r2c,
+                    fftwf_plan_dft_c2r, fftwf_plan_many_dft, fftwf_plan_many_dft_r2c,
+                    fftwf_plan_many_dft_c2r, fftwf_plan_r2r, fftwf_plan_guru64_dft,
+                    fftwf_plan_guru64_dft_r2c, fftwf_plan_guru64_dft_c2r,
+                    fftwf_plan_guru64_r2r};
+
+use std::libc::{c_int, c_uint, size_t};
+
+use super::{TransformData, Transform, Plan, Double, Single};
+
+/// A real scalar type with a version of fftw3 computing transforms over it.
+///
+/// Each planner calls the function of the same name in the fftw*_ family of the type,
+/// and returns a plan destroyed by the same family. The caller holds the lock of the
+/// planner, and checks the dimensions and the layout of the buffers. The planners return
+/// `None` when no plan satisfies the flags, as with `FFTW_WISDOM_ONLY`.
+pub trait FftwFloat: TransformData + Transform<Cmplx<Self>> + Neg<Self> {
+  unsafe fn alloc_complex(n: uint) -> *mut Cmplx<Self>;
+  unsafe fn plan_dft(rank: c_int, n: *c_int, input: *mut Cmplx<Self>,
+                     output: *mut Cmplx<Self>, sign: c_int, flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_dft_r2c(rank: c_int, n: *c_int, input: *mut Self,
+                         output: *mut Cmplx<Self>, flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_dft_c2r(rank: c_int, n: *c_int, input: *mut Cmplx<Self>,
+                         output: *mut Self, flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_many_dft(rank: c_int, n: *c_int, howmany: c_int, input: *mut Cmplx<Self>,
+                          inembed: *c_int, istride: c_int, idist: c_int,
+                          output: *mut Cmplx<Self>, onembed: *c_int, ostride: c_int,
+                          odist: c_int, sign: c_int, flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_many_dft_r2c(rank: c_int, n: *c_int, howmany: c_int, input: *mut Self,
+                              inembed: *c_int, istride: c_int, idist: c_int,
+                              output: *mut Cmplx<Self>, onembed: *c_int, ostride: c_int,
+                              odist: c_int, flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_many_dft_c2r(rank: c_int, n: *c_int, howmany: c_int,
+                              input: *mut Cmplx<Self>, inembed: *c_int, istride: c_int,
+                              idist: c_int, output: *mut Self, onembed: *c_int,
+                              ostride: c_int, odist: c_int, flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_r2r(rank: c_int, n: *c_int, input: *mut Self, output: *mut Self,
+                     kind: *fftw_r2r_kind, flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_guru64_dft(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut Cmplx<Self>,
+                            output: *mut Cmplx<Self>, sign: c_int,
+                            flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_guru64_dft_r2c(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64, input: *mut Self,
+                                output: *mut Cmplx<Self>, flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_guru64_dft_c2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64, input: *mut Cmplx<Self>,
+                                output: *mut Self, flags: c_uint) -> Option<Plan>;
+  unsafe fn plan_guru64_r2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut Self,
+                            output: *mut Self, kind: *fftw_r2r_kind,
+                            flags: c_uint) -> Option<Plan>;
+}
+
+impl FftwFloat for f64 {
+  #[inline]
+  unsafe fn alloc_complex(n: uint) -> *mut Cmplx<f64> {
+    fftw_alloc_complex(n as size_t)
+  }
+
+  #[inline]
+  unsafe fn plan_dft(rank: c_int, n: *c_int, input: *mut Cmplx<f64>,
+                     output: *mut Cmplx<f64>, sign: c_int,
+                     flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_dft(rank, n, input, output, sign, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_dft_r2c(rank: c_int, n: *c_int, input: *mut f64, output: *mut Cmplx<f64>,
+                         flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_dft_r2c(rank, n, input, output, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_dft_c2r(rank: c_int, n: *c_int, input: *mut Cmplx<f64>, output: *mut f64,
+                         flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_dft_c2r(rank, n, input, output, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft(rank: c_int, n: *c_int, howmany: c_int, input: *mut Cmplx<f64>,
+                          inembed: *c_int, istride: c_int, idist: c_int,
+                          output: *mut Cmplx<f64>, onembed: *c_int, ostride: c_int,
+                          odist: c_int, sign: c_int, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_many_dft(rank, n, howmany, input, inembed, istride, idist, output,
+                                 onembed, ostride, odist, sign, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft_r2c(rank: c_int, n: *c_int, howmany: c_int, input: *mut f64,
+                              inembed: *c_int, istride: c_int, idist: c_int,
+                              output: *mut Cmplx<f64>, onembed: *c_int, ostride: c_int,
+                              odist: c_int, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_many_dft_r2c(rank, n, howmany, input, inembed, istride, idist,
+                                     output, onembed, ostride, odist, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft_c2r(rank: c_int, n: *c_int, howmany: c_int,
+                              input: *mut Cmplx<f64>, inembed: *c_int, istride: c_int,
+                              idist: c_int, output: *mut f64, onembed: *c_int,
+                              ostride: c_int, odist: c_int, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_many_dft_c2r(rank, n, howmany, input, inembed, istride, idist,
+                                     output, onembed, ostride, odist, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_r2r(rank: c_int, n: *c_int, input: *mut f64, output: *mut f64,
+                     kind: *fftw_r2r_kind, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_r2r(rank, n, input, output, kind, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut Cmplx<f64>,
+                            output: *mut Cmplx<f64>, sign: c_int,
+                            flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_guru64_dft(rank, dims, howmany_rank, howmany_dims, input, output,
+                                   sign, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft_r2c(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64, input: *mut f64,
+                                output: *mut Cmplx<f64>, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_guru64_dft_r2c(rank, dims, howmany_rank, howmany_dims, input,
+                                       output, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft_c2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64, input: *mut Cmplx<f64>,
+                                output: *mut f64, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_guru64_dft_c2r(rank, dims, howmany_rank, howmany_dims, input,
+                                       output, flags), Double)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_r2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut f64,
+                            output: *mut f64, kind: *fftw_r2r_kind,
+                            flags: c_uint) -> Option<Plan> {
+    Plan::new(fftw_plan_guru64_r2r(rank, dims, howmany_rank, howmany_dims, input, output,
+                                   kind, flags), Double)
+  }
+}
+
+impl FftwFloat for f32 {
+  #[inline]
+  unsafe fn alloc_complex(n: uint) -> *mut Cmplx<f32> {
+    fftwf_alloc_complex(n as size_t)
+  }
+
+  #[inline]
+  unsafe fn plan_dft(rank: c_int, n: *c_int, input: *mut Cmplx<f32>,
+                     output: *mut Cmplx<f32>, sign: c_int,
+                     flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_dft(rank, n, input, output, sign, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_dft_r2c(rank: c_int, n: *c_int, input: *mut f32, output: *mut Cmplx<f32>,
+                         flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_dft_r2c(rank, n, input, output, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_dft_c2r(rank: c_int, n: *c_int, input: *mut Cmplx<f32>, output: *mut f32,
+                         flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_dft_c2r(rank, n, input, output, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft(rank: c_int, n: *c_int, howmany: c_int, input: *mut Cmplx<f32>,
+                          inembed: *c_int, istride: c_int, idist: c_int,
+                          output: *mut Cmplx<f32>, onembed: *c_int, ostride: c_int,
+                          odist: c_int, sign: c_int, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_many_dft(rank, n, howmany, input, inembed, istride, idist,
+                                  output, onembed, ostride, odist, sign, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft_r2c(rank: c_int, n: *c_int, howmany: c_int, input: *mut f32,
+                              inembed: *c_int, istride: c_int, idist: c_int,
+                              output: *mut Cmplx<f32>, onembed: *c_int, ostride: c_int,
+                              odist: c_int, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_many_dft_r2c(rank, n, howmany, input, inembed, istride, idist,
+                                      output, onembed, ostride, odist, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft_c2r(rank: c_int, n: *c_int, howmany: c_int,
+                              input: *mut Cmplx<f32>, inembed: *c_int, istride: c_int,
+                              idist: c_int, output: *mut f32, onembed: *c_int,
+                              ostride: c_int, odist: c_int, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_many_dft_c2r(rank, n, howmany, input, inembed, istride, idist,
+                                      output, onembed, ostride, odist, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_r2r(rank: c_int, n: *c_int, input: *mut f32, output: *mut f32,
+                     kind: *fftw_r2r_kind, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_r2r(rank, n, input, output, kind, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut Cmplx<f32>,
+                            output: *mut Cmplx<f32>, sign: c_int,
+                            flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_guru64_dft(rank, dims, howmany_rank, howmany_dims, input, output,
+                                    sign, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft_r2c(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64, input: *mut f32,
+                                output: *mut Cmplx<f32>, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_guru64_dft_r2c(rank, dims, howmany_rank, howmany_dims, input,
+                                        output, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft_c2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64, input: *mut Cmplx<f32>,
+                                output: *mut f32, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_guru64_dft_c2r(rank, dims, howmany_rank, howmany_dims, input,
+                                        output, flags), Single)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_r2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut f32,
+                            output: *mut f32, kind: *fftw_r2r_kind,
+                            flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwf_plan_guru64_r2r(rank, dims, howmany_rank, howmany_dims, input, output,
+                                    kind, flags), Single)
+  }
+}
@@ -0,0 +1,336 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Transforms over arbitrary memory layouts, through the guru interface of fftw3.
+//!
+//! A `GuruLayout` describes the dimensions of the transform, and the dimensions of the
+//! loop of transforms to compute ('howmany'), each with its own input and output stride.
+//! This makes it possible to transform a strided sub-block of a larger array without
+//! copying it : the buffers of the transform hold the whole arrays, and the layout
+//! starts at the given offsets.
+//!
+//! ```rust
+//! use fftw3_rust::{Fftw, GuruLayout, IoDim};
+//!
+//! // Transform the 4x4 block in the top-left corner of a 8x8 array.
+//! let layout = GuruLayout::new(~[IoDim { n: 4, is: 8, os: 4 },
+//!                                IoDim { n: 4, is: 1, os: 1 }], ~[]);
+//! let mut fftw = Fftw::new_guru(&layout, 64, 16).unwrap();
+//! ```
+
+use num::complex::Cmplx;
+
+use fftw3_bindgen::{FFTW_FORWARD, FFTW_BACKWARD, ptrdiff_t, fftw_iodim64,
+                    Struct_fftw_iodim64_do_not_use_me, fftw_r2r_kind};
+
+use std::libc::c_int;
+
+use super::{Fftw, FftBuf, FftwError, FftwFloat, TransformBuf, R2rKind, ZeroRank,
+            ZeroDimension, SizeMismatch, OutOfBounds, Estimate, ToPlanner, precision_of};
+use fftw3_cache::{plan_cached, describe_layout};
+use fftw3_r2r::describe_kinds;
+
+/// One dimension of a guru layout : 'n' values, 'is' elements apart in the input and
+/// 'os' elements apart in the output.
+/// The fields are public on purpose, so that dimensions can be written as struct
+/// literals. They are only checked once the layout is used to plan a transform.
+#[deriving(Eq, Clone)]
+pub struct IoDim {
+  n: uint,
+  is: int,
+  os: int,
+}
+
+/// Describes where the values of a transform are located in its input and output
+/// buffers.
+#[deriving(Clone)]
+pub struct GuruLayout {
+  // Dimensions of the transform.
+  priv dims: ~[IoDim],
+  // Dimensions of the loop of transforms computed by the plan.
+  priv howmany: ~[IoDim],
+  // Position of the first input value in the input buffer.
+  priv in_offset: uint,
+  // Position of the first output value in the output buffer.
+  priv out_offset: uint,
+}
+
+impl GuruLayout {
+  /// Creates a layout starting at the beginning of the buffers.
+  pub fn new(dims: ~[IoDim], howmany: ~[IoDim]) -> GuruLayout {
+    GuruLayout {
+      dims: dims,
+      howmany: howmany,
+      in_offset: 0,
+      out_offset: 0,
+    }
+  }
+
+  /// Returns the same layout starting at the given offsets in the buffers.
+  pub fn with_offsets(self, in_offset: uint, out_offset: uint) -> GuruLayout {
+    GuruLayout {
+      in_offset: in_offset,
+      out_offset: out_offset,
+      .. self
+    }
+  }
+
+  /// Checks that every value of the layout lies in buffers of 'in_len' and 'out_len'
+  /// elements, for a complex transform.
+  pub fn check(&self, in_len: uint, out_len: uint) -> Result<(), FftwError> {
+    self.check_bounds(in_len, false, out_len, false)
+  }
+
+  /// Checks the layout against buffers of 'in_len' and 'out_len' elements. The last
+  /// dimension of the complex side of a real transform only holds n/2+1 values, which
+  /// 'half_in' and 'half_out' account for.
+  fn check_bounds(&self, in_len: uint, half_in: bool, out_len: uint, half_out: bool)
+                  -> Result<(), FftwError> {
+    if self.dims.is_empty() {
+      return Err(ZeroRank);
+    }
+    match self.dims.iter().chain(self.howmany.iter()).position(|d| d.n == 0) {
+      Some(axis) => return Err(ZeroDimension(axis)),
+      None => (),
+    }
+
+    let (in_lo, in_hi) = self.bounds(self.in_offset, half_in, |d| d.is);
+    let (out_lo, out_hi) = self.bounds(self.out_offset, half_out, |d| d.os);
+    if in_lo < 0 || in_hi >= in_len as int || out_lo < 0 || out_hi >= out_len as int {
+      Err(OutOfBounds)
+    } else {
+      Ok(())
+    }
+  }
+
+  /// Returns the positions of the first and last values reached by the layout.
+  fn bounds(&self, offset: uint, half: bool, stride: |&IoDim| -> int) -> (int, int) {
+    let last = self.dims.len() - 1;
+    let (mut lo, mut hi) = (offset as int, offset as int);
+    let mut spans = ::std::vec::with_capacity(self.dims.len() + self.howmany.len());
+    for (i, d) in self.dims.iter().enumerate() {
+      let n = if half && i == last { d.n/2 + 1 } else { d.n };
+      spans.push((n as int - 1) * stride(d));
+    }
+    for d in self.howmany.iter() {
+      spans.push((d.n as int - 1) * stride(d));
+    }
+    for &span in spans.iter() {
+      if span < 0 {
+        lo += span;
+      } else {
+        hi += span;
+      }
+    }
+    (lo, hi)
+  }
+
+  /// Returns the dimensions and the offsets of the layout, identifying it in a plan
+  /// cache.
+  fn describe(&self) -> ~str {
+    format!("dims {}, loop {}, offsets {}/{}", describe_dims(self.dims),
+            describe_dims(self.howmany), self.in_offset, self.out_offset)
+  }
+
+  fn shape(&self) -> ~[uint] {
+    self.dims.iter().map(|d| d.n).collect()
+  }
+
+  fn fftw_dims(&self) -> (~[fftw_iodim64], ~[fftw_iodim64]) {
+    let convert = |d: &IoDim| Struct_fftw_iodim64_do_not_use_me {
+      n: d.n as ptrdiff_t,
+      is: d.is as ptrdiff_t,
+      os: d.os as ptrdiff_t,
+    };
+    (self.dims.iter().map(|d| convert(d)).collect(),
+     self.howmany.iter().map(|d| convert(d)).collect())
+  }
+}
+
+/// Returns the size and the strides of each of 'dims', as n:is:os.
+fn describe_dims(dims: &[IoDim]) -> ~str {
+  let dims: ~[~str] = dims.iter().map(|d| format!("{}:{}:{}", d.n, d.is, d.os)).collect();
+  dims.connect(",")
+}
+
+impl<F: FftwFloat> Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>> {
+  /// Prepare a new transform over the values described by 'layout', in buffers of
+  /// 'in_len' and 'out_len' complex values.
+  /// Returns an error if the layout reaches values outside of the buffers.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_guru(layout: &GuruLayout, in_len: uint, out_len: uint)
+                  -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_guru_with(layout, in_len, out_len, Estimate)
+  }
+
+  /// Same as `new_guru`, planned with the options of 'planner'.
+  pub fn new_guru_with<P: ToPlanner>(layout: &GuruLayout, in_len: uint, out_len: uint,
+                                     planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    if_ok!(layout.check(in_len, out_len));
+    Fftw::new_guru_dft(layout, in_len, out_len, FFTW_FORWARD, planner)
+  }
+
+  /// Prepare a new inverse transform over the values described by 'layout', in buffers
+  /// of 'in_len' and 'out_len' complex values.
+  /// Returns an error if the layout reaches values outside of the buffers.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_guru_inv(layout: &GuruLayout, in_len: uint, out_len: uint)
+                      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_guru_inv_with(layout, in_len, out_len, Estimate)
+  }
+
+  /// Same as `new_guru_inv`, planned with the options of 'planner'.
+  pub fn new_guru_inv_with<P: ToPlanner>(layout: &GuruLayout, in_len: uint, out_len: uint,
+                                         planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    if_ok!(layout.check(in_len, out_len));
+    Fftw::new_guru_dft(layout, in_len, out_len, FFTW_BACKWARD, planner)
+  }
+
+  fn new_guru_dft<P: ToPlanner>(layout: &GuruLayout, in_len: uint, out_len: uint, sign: c_int,
+                                planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>, FftwError> {
+    let _in: FftBuf<Cmplx<F>> = TransformBuf::new(in_len);
+    let _out: FftBuf<Cmplx<F>> = TransformBuf::new(out_len);
+    let (dims, howmany) = layout.fftw_dims();
+    let kind = if sign == FFTW_FORWARD { "forward" } else { "backward" };
+    let transform = describe_layout(kind, layout.shape(), layout.describe(), false);
+    let _p = if_ok!(plan_cached(&planner.to_planner(), precision_of::<F>(), transform,
+                                |flags| unsafe {
+      FftwFloat::plan_guru64_dft(dims.len() as c_int, dims.as_ptr(),
+                                 howmany.len() as c_int, howmany.as_ptr(),
+                                 _in.data.offset(layout.in_offset as int),
+                                 _out.data.offset(layout.out_offset as int),
+                                 sign, flags)
+    }));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: layout.shape(),
+      offsets: (layout.in_offset, layout.out_offset),
+      rows: None,
+    })
+  }
+}
+
+impl<F: FftwFloat> Fftw<FftBuf<F>, FftBuf<Cmplx<F>>> {
+  /// Prepare a new real to complex transform over the values described by 'layout', in
+  /// buffers of 'in_len' real values and 'out_len' complex values. The last dimension
+  /// of the output only holds n/2+1 values.
+  /// Returns an error if the layout reaches values outside of the buffers.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_guru_r2c(layout: &GuruLayout, in_len: uint, out_len: uint)
+                      -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    Fftw::new_guru_r2c_with(layout, in_len, out_len, Estimate)
+  }
+
+  /// Same as `new_guru_r2c`, planned with the options of 'planner'.
+  pub fn new_guru_r2c_with<P: ToPlanner>(layout: &GuruLayout, in_len: uint, out_len: uint,
+                                         planner: P)
+      -> Result<Fftw<FftBuf<F>, FftBuf<Cmplx<F>>>, FftwError> {
+    if_ok!(layout.check_bounds(in_len, false, out_len, true));
+    let _in: FftBuf<F> = TransformBuf::new(in_len);
+    let _out: FftBuf<Cmplx<F>> = TransformBuf::new(out_len);
+    let (dims, howmany) = layout.fftw_dims();
+    let transform = describe_layout("r2c", layout.shape(), layout.describe(), false);
+    let _p = if_ok!(plan_cached(&planner.to_planner(), precision_of::<F>(), transform,
+                                |flags| unsafe {
+      FftwFloat::plan_guru64_dft_r2c(dims.len() as c_int, dims.as_ptr(),
+                                     howmany.len() as c_int, howmany.as_ptr(),
+                                     _in.data.offset(layout.in_offset as int),
+                                     _out.data.offset(layout.out_offset as int),
+                                     flags)
+    }));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: layout.shape(),
+      offsets: (layout.in_offset, layout.out_offset),
+      rows: None,
+    })
+  }
+}
+
+impl<F: FftwFloat> Fftw<FftBuf<Cmplx<F>>, FftBuf<F>> {
+  /// Prepare a new complex to real transform over the values described by 'layout', in
+  /// buffers of 'in_len' complex values and 'out_len' real values. The last dimension
+  /// of the input only holds n/2+1 values.
+  /// Returns an error if the layout reaches values outside of the buffers.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_guru_c2r(layout: &GuruLayout, in_len: uint, out_len: uint)
+                      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    Fftw::new_guru_c2r_with(layout, in_len, out_len, Estimate)
+  }
+
+  /// Same as `new_guru_c2r`, planned with the options of 'planner'.
+  pub fn new_guru_c2r_with<P: ToPlanner>(layout: &GuruLayout, in_len: uint, out_len: uint,
+                                         planner: P)
+      -> Result<Fftw<FftBuf<Cmplx<F>>, FftBuf<F>>, FftwError> {
+    if_ok!(layout.check_bounds(in_len, true, out_len, false));
+    let _in: FftBuf<Cmplx<F>> = TransformBuf::new(in_len);
+    let _out: FftBuf<F> = TransformBuf::new(out_len);
+    let (dims, howmany) = layout.fftw_dims();
+    let transform = describe_layout("c2r", layout.shape(), layout.describe(), false);
+    let _p = if_ok!(plan_cached(&planner.to_planner(), precision_of::<F>(), transform,
+                                |flags| unsafe {
+      FftwFloat::plan_guru64_dft_c2r(dims.len() as c_int, dims.as_ptr(),
+                                     howmany.len() as c_int, howmany.as_ptr(),
+                                     _in.data.offset(layout.in_offset as int),
+                                     _out.data.offset(layout.out_offset as int),
+                                     flags)
+    }));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: layout.shape(),
+      offsets: (layout.in_offset, layout.out_offset),
+      rows: None,
+    })
+  }
+}
+
+impl<F: FftwFloat> Fftw<FftBuf<F>, FftBuf<F>> {
+  /// Prepare a new real to real transform over the values described by 'layout', in
+  /// buffers of 'in_len' and 'out_len' values, with one kind of transform per dimension.
+  /// Returns an error if the layout reaches values outside of the buffers, or if there
+  /// is not exactly one kind per dimension.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_guru_r2r(layout: &GuruLayout, kinds: &[R2rKind], in_len: uint, out_len: uint)
+                      -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_guru_r2r_with(layout, kinds, in_len, out_len, Estimate)
+  }
+
+  /// Same as `new_guru_r2r`, planned with the options of 'planner'.
+  pub fn new_guru_r2r_with<P: ToPlanner>(layout: &GuruLayout, kinds: &[R2rKind],
+                                         in_len: uint, out_len: uint, planner: P)
+      -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    if_ok!(layout.check(in_len, out_len));
+    if kinds.len() != layout.dims.len() {
+      return Err(SizeMismatch(layout.dims.len(), kinds.len()));
+    }
+    let _in: FftBuf<F> = TransformBuf::new(in_len);
+    let _out: FftBuf<F> = TransformBuf::new(out_len);
+    let (dims, howmany) = layout.fftw_dims();
+    let fftw_kinds: ~[fftw_r2r_kind] = kinds.iter().map(|k| k.fftw_kind()).collect();
+    let description = format!("{}, {}", layout.describe(), describe_kinds(kinds));
+    let transform = describe_layout("r2r", layout.shape(), description, false);
+    let _p = if_ok!(plan_cached(&planner.to_planner(), precision_of::<F>(), transform,
+                                |flags| unsafe {
+      FftwFloat::plan_guru64_r2r(dims.len() as c_int, dims.as_ptr(),
+                                 howmany.len() as c_int, howmany.as_ptr(),
+                                 _in.data.offset(layout.in_offset as int),
+                                 _out.data.offset(layout.out_offset as int),
+                                 fftw_kinds.as_ptr(), flags)
+    }));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: layout.shape(),
+      offsets: (layout.in_offset, layout.out_offset),
+      rows: None,
+    })
+  }
+}
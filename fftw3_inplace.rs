@@ -0,0 +1,231 @@
+// Copyright (c) 2014 Raphael Catolino
+//! In-place transforms, where the output overwrites the input in a single buffer.
+//!
+//! For real transforms the buffer holds the n/2+1 complex values of the last dimension,
+//! so seen as real values each row holds 2*(n/2+1) values : the n values of the real
+//! array, followed by one or two padding values.
+//!
+//! As `Fftw`, the transforms are computed in the precision of their scalar type.
+//!
+//! ```rust
+//! use fftw3_rust::InPlaceFftw;
+//!
+//! let mut fftw: InPlaceFftw<f64> = InPlaceFftw::new_r2c([2, 3]).unwrap();
+//! for (i, row) in fftw.mut_real_rows().enumerate() {
+//!   for j in range(0, 3) {
+//!     row[j] = (3*i + j) as f64;
+//!   }
+//! }
+//! fftw.compute();
+//! println!("{}", fftw.cmplx());
+//! ```
+
+use num::complex::Cmplx;
+
+use fftw3_bindgen::{FFTW_FORWARD, FFTW_BACKWARD, fftw_alignment_of};
+
+use std::cast::transmute;
+use std::libc::c_void;
+use std::ptr::set_memory;
+use std::vec::{Chunks, MutChunks};
+
+use super::{FftBuf, FftwError, FftwFloat, TransformBuf, SizeMismatch, Misaligned,
+            check_shape, half_shape, plan_dft, plan_dft_r2c, plan_dft_c2r, CplxSlice, Plan,
+            Estimate, ToPlanner, TransformKind, Forward, Backward, RealToComplex,
+            ComplexToReal};
+
+/// Holds the state of a transform computed in a single buffer.
+pub struct InPlaceFftw<F> {
+  priv data: FftBuf<Cmplx<F>>,
+  priv plan: Plan,
+  priv kind: TransformKind,
+  priv shape: ~[uint],
+}
+
+impl<F: FftwFloat> InPlaceFftw<F> {
+  /// Prepare a new in-place transform over an array of complex values of arbitrary
+  /// rank, stored in row-major order.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  pub fn new(shape: &[uint]) -> Result<InPlaceFftw<F>, FftwError> {
+    InPlaceFftw::new_with(shape, Estimate)
+  }
+
+  /// Same as `new`, planned with the options of 'planner'.
+  pub fn new_with<P: ToPlanner>(shape: &[uint], planner: P)
+                                -> Result<InPlaceFftw<F>, FftwError> {
+    InPlaceFftw::plan(shape, Forward, planner)
+  }
+
+  /// Prepare a new in-place inverse transform over an array of complex values of
+  /// arbitrary rank, stored in row-major order.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  pub fn new_inv(shape: &[uint]) -> Result<InPlaceFftw<F>, FftwError> {
+    InPlaceFftw::new_inv_with(shape, Estimate)
+  }
+
+  /// Same as `new_inv`, planned with the options of 'planner'.
+  pub fn new_inv_with<P: ToPlanner>(shape: &[uint], planner: P)
+                                    -> Result<InPlaceFftw<F>, FftwError> {
+    InPlaceFftw::plan(shape, Backward, planner)
+  }
+
+  /// Prepare a new in-place transform over an array of real values of arbitrary rank,
+  /// stored in row-major order with padded rows. Only the n/2+1 first complex values of
+  /// the last dimension are computed.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  pub fn new_r2c(shape: &[uint]) -> Result<InPlaceFftw<F>, FftwError> {
+    InPlaceFftw::new_r2c_with(shape, Estimate)
+  }
+
+  /// Same as `new_r2c`, planned with the options of 'planner'.
+  pub fn new_r2c_with<P: ToPlanner>(shape: &[uint], planner: P)
+                                    -> Result<InPlaceFftw<F>, FftwError> {
+    InPlaceFftw::plan(shape, RealToComplex, planner)
+  }
+
+  /// Prepare a new in-place inverse transform producing an array of real values of
+  /// arbitrary rank, stored in row-major order with padded rows. The input only holds
+  /// the n/2+1 first complex values of the last dimension.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  pub fn new_c2r(shape: &[uint]) -> Result<InPlaceFftw<F>, FftwError> {
+    InPlaceFftw::new_c2r_with(shape, Estimate)
+  }
+
+  /// Same as `new_c2r`, planned with the options of 'planner'.
+  pub fn new_c2r_with<P: ToPlanner>(shape: &[uint], planner: P)
+                                    -> Result<InPlaceFftw<F>, FftwError> {
+    InPlaceFftw::plan(shape, ComplexToReal, planner)
+  }
+
+  fn plan<P: ToPlanner>(shape: &[uint], kind: TransformKind,
+                        planner: P) -> Result<InPlaceFftw<F>, FftwError> {
+    let size = if_ok!(check_shape(shape));
+    let capacity = match kind {
+      Forward | Backward => size,
+      RealToComplex | ComplexToReal => half_shape(shape).iter().fold(1u, |a, &b| a * b),
+    };
+
+    let mut data: FftBuf<Cmplx<F>> = TransformBuf::new(capacity);
+    let p = data.data;
+    let planner = planner.to_planner();
+    let plan = if_ok!(match kind {
+      Forward => plan_dft(shape, p, p, FFTW_FORWARD, &planner),
+      Backward => plan_dft(shape, p, p, FFTW_BACKWARD, &planner),
+      RealToComplex => plan_dft_r2c(shape, p as *mut F, p, &planner),
+      ComplexToReal => plan_dft_c2r(shape, p, p as *mut F, &planner),
+    });
+    // The planner may have overwritten the buffer, it is cleared once the plan is made.
+    data.mark_filled();
+    unsafe {
+      set_memory(p, 0, capacity);
+    }
+
+    Ok(InPlaceFftw {
+      data: data,
+      plan: plan,
+      kind: kind,
+      shape: shape.to_owned(),
+    })
+  }
+
+  /// Perform the actual Fourier transform computation, overwriting the input values.
+  pub fn compute(&mut self) {
+    unsafe {
+      self.execute_on(self.data.data);
+    }
+  }
+
+  /// Compute the transform of the values in 'data', in place, with the plan of this
+  /// transform. The internal buffer is left untouched.
+  /// The array must have the length and the alignment of the internal buffer (arrays
+  /// allocated by fftw3 are always properly aligned).
+  pub fn compute_with(&self, data: &mut [Cmplx<F>]) -> Result<(), FftwError> {
+    if data.len() != self.data.capacity {
+      return Err(SizeMismatch(self.data.capacity, data.len()));
+    }
+
+    unsafe {
+      let p = data.as_mut_ptr();
+      if fftw_alignment_of(p as *mut f64) != fftw_alignment_of(self.data.data as *mut f64) {
+        return Err(Misaligned);
+      }
+      self.execute_on(p);
+    }
+    Ok(())
+  }
+
+  /// Computes the transform in place on the array at 'p', with the new-array execute
+  /// functions : the plan may be shared with the plan memo.
+  unsafe fn execute_on(&self, p: *mut Cmplx<F>) {
+    let p = p as *mut c_void;
+    match self.kind {
+      Forward | Backward => self.plan.execute_raw(p, true, p, true),
+      RealToComplex => self.plan.execute_raw(p, false, p, true),
+      ComplexToReal => self.plan.execute_raw(p, true, p, false),
+    }
+  }
+
+  #[inline]
+  /// Returns the dimensions of the transform, in row-major order.
+  pub fn shape<'a>(&'a self) -> &'a [uint] {
+    self.shape.as_slice()
+  }
+
+  #[inline]
+  /// Returns an immutable view of the buffer, as complex values.
+  pub fn cmplx<'a>(&'a self) -> &'a [Cmplx<F>] {
+    self.data.as_slice()
+  }
+
+  #[inline]
+  /// Returns a mutable view of the buffer, as complex values.
+  pub fn mut_cmplx<'a>(&'a mut self) -> &'a mut [Cmplx<F>] {
+    self.data.as_mut_slice()
+  }
+
+  #[inline]
+  /// Returns an immutable view of the buffer, as real values. For real transforms the
+  /// rows are padded, see `real_rows`.
+  pub fn real<'a>(&'a self) -> &'a [F] {
+    unsafe {
+      transmute(CplxSlice {
+        data: self.data.data as *F,
+        len: 2*self.data.capacity,
+      })
+    }
+  }
+
+  #[inline]
+  /// Returns a mutable view of the buffer, as real values. For real transforms the
+  /// rows are padded, see `mut_real_rows`.
+  pub fn mut_real<'a>(&'a mut self) -> &'a mut [F] {
+    unsafe {
+      transmute(CplxSlice {
+        data: self.data.data as *F,
+        len: 2*self.data.capacity,
+      })
+    }
+  }
+
+  /// Creates an iterator over the rows of the buffer seen as real values. Each row
+  /// holds the n values of the last dimension followed by the padding.
+  pub fn real_rows<'a>(&'a self) -> Chunks<'a, F> {
+    let len = self.real_row_len();
+    self.real().chunks(len)
+  }
+
+  /// Creates an iterator over the mutable rows of the buffer seen as real values. Each
+  /// row holds the n values of the last dimension followed by the padding.
+  pub fn mut_real_rows<'a>(&'a mut self) -> MutChunks<'a, F> {
+    let len = self.real_row_len();
+    self.mut_real().mut_chunks(len)
+  }
+
+  fn real_row_len(&self) -> uint {
+    let last = self.shape[self.shape.len() - 1];
+    match self.kind {
+      Forward | Backward => 2*last,
+      RealToComplex | ComplexToReal => 2*(last/2 + 1),
+    }
+  }
+}
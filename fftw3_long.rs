@@ -0,0 +1,293 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Extended precision transforms, computed by the long double version of fftw3.
+//!
+//! `LongDouble` holds a value of the 80-bit extended precision type of x86_64. Rust
+//! has no arithmetic on this type, the values are meant to be converted from `f64`
+//! before the transform and back to `f64` after it : only the transform itself is
+//! computed with a 64 bits mantissa.
+//!
+//! ```rust
+//! use fftw3_rust::{Fftw, LongDouble};
+//!
+//! let input: ~[LongDouble] = [1f64, 0f64, 2f64, 4f64].iter().map(|&x| {
+//!   LongDouble::from_f64(x)
+//! }).collect();
+//! let mut fftw = Fftw::from_slice(input);
+//! for cx in fftw.compute().unwrap().iter() {
+//!   println!("{} {}", cx.re.to_f64(), cx.im.to_f64());
+//! }
+//! ```
+
+use num::complex::Cmplx;
+
+use fftw3_bindgen::{c_long_double, fftwl_complex, fftw_iodim64, fftw_r2r_kind,
+                    fftwl_alloc_real, fftwl_alloc_complex, fftwl_free, fftwl_plan_dft,
+                    fftwl_plan_dft_r2c, fftwl_plan_dft_c2r, fftwl_plan_many_dft,
+                    fftwl_plan_many_dft_r2c, fftwl_plan_many_dft_c2r, fftwl_plan_r2r,
+                    fftwl_plan_guru64_dft, fftwl_plan_guru64_dft_r2c,
+                    fftwl_plan_guru64_dft_c2r, fftwl_plan_guru64_r2r};
+
+use std::cast::transmute;
+use std::fmt::{Show, Formatter};
+use std::fmt;
+use std::libc::{c_int, c_uint, c_void, size_t};
+
+use fftw3_convert::{FRAC_MASK, F64_INFINITY, round_to_f64};
+
+use super::{TransformData, Transform, FftwFloat, Plan, Precision, Extended, plan_dft_r2c,
+            plan_dft_c2r, Planner, FftwError};
+
+/// A value of the 80-bit extended precision type, stored like a C long double.
+#[deriving(Clone)]
+pub struct LongDouble {
+  priv mantissa: u64,
+  priv sign_exp: u16,
+  priv pad0: u16,
+  priv pad1: u32,
+}
+
+impl LongDouble {
+  /// Converts a f64 to extended precision. Every f64 value is represented exactly.
+  pub fn from_f64(x: f64) -> LongDouble {
+    let bits: u64 = unsafe { transmute(x) };
+    let sign = (bits >> 48) as u16 & 0x8000;
+    let exp = (bits >> 52) as u16 & 0x7ff;
+    let frac = bits & FRAC_MASK;
+    let (exp, mantissa) = if exp == 0x7ff {
+      (0x7fff, 1 << 63 | frac << 11)
+    } else if exp != 0 {
+      (exp + 16383 - 1023, 1 << 63 | frac << 11)
+    } else if frac == 0 {
+      (0, 0)
+    } else {
+      // Subnormal values are normalized, the extended exponent is large enough.
+      let (mut exp, mut mantissa) = (15372u16, frac);
+      while mantissa & (1 << 63) == 0 {
+        mantissa <<= 1;
+        exp -= 1;
+      }
+      (exp, mantissa)
+    };
+
+    LongDouble {
+      mantissa: mantissa,
+      sign_exp: sign | exp,
+      pad0: 0,
+      pad1: 0,
+    }
+  }
+
+  /// Converts this value to a f64, rounding to the nearest (ties to even). Values too
+  /// large for a f64 become infinite, and values too small become zero.
+  pub fn to_f64(&self) -> f64 {
+    let sign = (self.sign_exp as u64 & 0x8000) << 48;
+    let exp = (self.sign_exp & 0x7fff) as int;
+    let m = self.mantissa;
+    let bits = if exp == 0x7fff {
+      if m << 1 == 0 {
+        F64_INFINITY
+      } else {
+        F64_INFINITY | 1 << 51 | (m >> 11) & FRAC_MASK
+      }
+    } else if m & (1 << 63) == 0 {
+      // Denormals are far below the range of f64, unnormals are invalid values.
+      if exp == 0 || m == 0 { 0 } else { F64_INFINITY | 1 << 51 }
+    } else {
+      round_to_f64(exp - 16383, m, false)
+    };
+    unsafe { transmute(sign | bits) }
+  }
+}
+
+impl Show for LongDouble {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f.buf, "{}", self.to_f64())
+  }
+}
+
+/// Negation only flips the sign bit, which is exact.
+impl Neg<LongDouble> for LongDouble {
+  fn neg(&self) -> LongDouble {
+    LongDouble {
+      sign_exp: self.sign_exp ^ 0x8000,
+      .. self.clone()
+    }
+  }
+}
+
+impl fmt::Float for LongDouble {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    fmt::Float::fmt(&self.to_f64(), f)
+  }
+}
+
+/// Converts a complex value to extended precision.
+pub fn cmplx_to_long(cx: &Cmplx<f64>) -> Cmplx<LongDouble> {
+  Cmplx {
+    re: LongDouble::from_f64(cx.re),
+    im: LongDouble::from_f64(cx.im),
+  }
+}
+
+/// Converts a complex value from extended precision, rounding each part to the nearest
+/// f64.
+pub fn long_to_cmplx(cx: &Cmplx<LongDouble>) -> Cmplx<f64> {
+  Cmplx::new(cx.re.to_f64(), cx.im.to_f64())
+}
+
+impl TransformData for LongDouble {
+  #[inline]
+  fn fftw_alloc(capacity: uint) -> *mut LongDouble {
+    unsafe {
+      fftwl_alloc_real(capacity as size_t) as *mut LongDouble
+    }
+  }
+
+  #[inline]
+  fn fftw_free(data: *mut LongDouble) {
+    unsafe {
+      fftwl_free(data as *mut c_void);
+    }
+  }
+
+  #[inline]
+  fn transform_size(input_capacity: uint, _: &[LongDouble]) -> uint {
+    input_capacity/2 + 1
+  }
+
+  #[inline]
+  fn is_complex(_: &[LongDouble]) -> bool {
+    false
+  }
+
+  #[inline]
+  fn precision(_: &[LongDouble]) -> Precision {
+    Extended
+  }
+}
+
+impl Transform<Cmplx<LongDouble>> for LongDouble {
+  #[inline]
+  fn plan(N: uint, input: *mut LongDouble, output: *mut Cmplx<LongDouble>,
+          planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft_r2c([N], input, output, planner)
+  }
+
+  #[inline]
+  fn plan_inv(N: uint, input: *mut Cmplx<LongDouble>, output: *mut LongDouble,
+              planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft_c2r([N], input, output, planner)
+  }
+}
+
+impl FftwFloat for LongDouble {
+  #[inline]
+  unsafe fn alloc_complex(n: uint) -> *mut Cmplx<LongDouble> {
+    fftwl_alloc_complex(n as size_t) as *mut Cmplx<LongDouble>
+  }
+
+  #[inline]
+  unsafe fn plan_dft(rank: c_int, n: *c_int, input: *mut Cmplx<LongDouble>,
+                     output: *mut Cmplx<LongDouble>, sign: c_int,
+                     flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_dft(rank, n, input as *mut fftwl_complex,
+                             output as *mut fftwl_complex, sign, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_dft_r2c(rank: c_int, n: *c_int, input: *mut LongDouble,
+                         output: *mut Cmplx<LongDouble>, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_dft_r2c(rank, n, input as *mut c_long_double,
+                                 output as *mut fftwl_complex, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_dft_c2r(rank: c_int, n: *c_int, input: *mut Cmplx<LongDouble>,
+                         output: *mut LongDouble, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_dft_c2r(rank, n, input as *mut fftwl_complex,
+                                 output as *mut c_long_double, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft(rank: c_int, n: *c_int, howmany: c_int,
+                          input: *mut Cmplx<LongDouble>, inembed: *c_int, istride: c_int,
+                          idist: c_int, output: *mut Cmplx<LongDouble>, onembed: *c_int,
+                          ostride: c_int, odist: c_int, sign: c_int,
+                          flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_many_dft(rank, n, howmany, input as *mut fftwl_complex, inembed,
+                                  istride, idist, output as *mut fftwl_complex, onembed,
+                                  ostride, odist, sign, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft_r2c(rank: c_int, n: *c_int, howmany: c_int,
+                              input: *mut LongDouble, inembed: *c_int, istride: c_int,
+                              idist: c_int, output: *mut Cmplx<LongDouble>,
+                              onembed: *c_int, ostride: c_int, odist: c_int,
+                              flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_many_dft_r2c(rank, n, howmany, input as *mut c_long_double,
+                                      inembed, istride, idist,
+                                      output as *mut fftwl_complex, onembed, ostride,
+                                      odist, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft_c2r(rank: c_int, n: *c_int, howmany: c_int,
+                              input: *mut Cmplx<LongDouble>, inembed: *c_int,
+                              istride: c_int, idist: c_int, output: *mut LongDouble,
+                              onembed: *c_int, ostride: c_int, odist: c_int,
+                              flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_many_dft_c2r(rank, n, howmany, input as *mut fftwl_complex,
+                                      inembed, istride, idist,
+                                      output as *mut c_long_double, onembed, ostride,
+                                      odist, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_r2r(rank: c_int, n: *c_int, input: *mut LongDouble,
+                     output: *mut LongDouble, kind: *fftw_r2r_kind,
+                     flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_r2r(rank, n, input as *mut c_long_double,
+                             output as *mut c_long_double, kind, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut Cmplx<LongDouble>,
+                            output: *mut Cmplx<LongDouble>, sign: c_int,
+                            flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_guru64_dft(rank, dims, howmany_rank, howmany_dims,
+                                    input as *mut fftwl_complex,
+                                    output as *mut fftwl_complex, sign, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft_r2c(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64, input: *mut LongDouble,
+                                output: *mut Cmplx<LongDouble>,
+                                flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_guru64_dft_r2c(rank, dims, howmany_rank, howmany_dims,
+                                        input as *mut c_long_double,
+                                        output as *mut fftwl_complex, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft_c2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64,
+                                input: *mut Cmplx<LongDouble>, output: *mut LongDouble,
+                                flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_guru64_dft_c2r(rank, dims, howmany_rank, howmany_dims,
+                                        input as *mut fftwl_complex,
+                                        output as *mut c_long_double, flags), Extended)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_r2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut LongDouble,
+                            output: *mut LongDouble, kind: *fftw_r2r_kind,
+                            flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwl_plan_guru64_r2r(rank, dims, howmany_rank, howmany_dims,
+                                    input as *mut c_long_double,
+                                    output as *mut c_long_double, kind, flags), Extended)
+  }
+}
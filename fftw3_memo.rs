@@ -0,0 +1,292 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Plan memo : reuses the plans of identical transforms within the process.
+//!
+//! Making a plan costs far more than computing a small transform, even with `Estimate`.
+//! Once enabled by `enable_memo`, the memo keeps the plans of the complex and real
+//! transforms of `Fftw` and `InPlaceFftw`, keyed by precision, kind, shape, planner
+//! flags, number of threads and alignment of the arrays, and hands them out again to the
+//! next transforms with the same key. A plan of the memo was made for other arrays, so it
+//! always computes through the new-array execute functions of fftw3.
+//!
+//! The memo holds at most `memo_capacity()` plans and evicts the least recently used ones
+//! beyond it. It is disabled until enabled, and destroyed along with the `MemoGuard`
+//! returned by `enable_memo`. A plan evicted, removed by `clear_memo` or held by the
+//! destroyed memo is destroyed once the last transform using it is dropped.
+//!
+//! ```rust
+//! use fftw3_rust::{Fftw, enable_memo, memo_stats};
+//!
+//! let _memo = enable_memo(64);
+//! for _ in range(0, 100) {
+//!   let mut fftw = Fftw::from_slice([1f64, 0f64, 2f64, 4f64]);
+//!   fftw.compute();
+//! }
+//! let stats = memo_stats();
+//! println!("{} plans made, {} reused", stats.misses, stats.hits);
+//! ```
+
+use fftw3_bindgen::fftw_alignment_of;
+use fftw3_cache::plan_cached;
+use fftw3_planner::Planner;
+
+use std::cast::transmute;
+use std::hashmap::HashMap;
+use std::libc::c_uint;
+use std::trie::TrieMap;
+
+use sync::Arc;
+use sync::mutex::{StaticMutex, MUTEX_INIT};
+
+use super::{FftwError, Plan, Precision};
+
+/// Serializes the uses of the memo. It is taken before `LOCK`, which planning a
+/// transform and destroying an evicted plan take.
+static mut MEMO_LOCK: StaticMutex = MUTEX_INIT;
+// Created by the first guard and destroyed with the last one, under MEMO_LOCK. The memo
+// is disabled while it is null.
+static mut MEMO: *mut Memo = 0 as *mut Memo;
+
+/// Counters of the memo since it was enabled, and number of plans it holds.
+#[deriving(Eq, Clone)]
+pub struct MemoStats {
+  /// Transforms which reused a plan of the memo.
+  hits: uint,
+  /// Transforms planned because the memo did not hold their plan.
+  misses: uint,
+  /// Plans evicted to stay within the capacity of the memo.
+  evictions: uint,
+  /// Plans held by the memo.
+  plans: uint,
+}
+
+/// Plans keyed by the transform they compute, at most 'capacity' of them. The process
+/// memo is one of them, kept behind MEMO_LOCK.
+pub struct Memo {
+  priv plans: HashMap<~str, MemoEntry>,
+  // The keys of the plans by last use, from the least recently used one.
+  priv order: TrieMap<~str>,
+  priv capacity: uint,
+  // Incremented on every use, to order the plans by last use.
+  priv clock: uint,
+  priv hits: uint,
+  priv misses: uint,
+  priv evictions: uint,
+  // Guards keeping the process memo enabled.
+  priv guards: uint,
+}
+
+struct MemoEntry {
+  plan: Arc<Plan>,
+  last_use: uint,
+}
+
+impl Memo {
+  /// Creates an empty memo holding at most 'capacity' plans.
+  pub fn new(capacity: uint) -> Memo {
+    Memo {
+      plans: HashMap::new(),
+      order: TrieMap::new(),
+      capacity: capacity,
+      clock: 0,
+      hits: 0,
+      misses: 0,
+      evictions: 0,
+      guards: 0,
+    }
+  }
+
+  /// Returns a plan sharing the one held for 'key', if any.
+  pub fn find(&mut self, key: &~str) -> Option<Plan> {
+    self.clock += 1;
+    let clock = self.clock;
+    match self.plans.find_mut(key) {
+      Some(entry) => {
+        Memo::touch(&mut self.order, entry, clock);
+        self.hits += 1;
+        Some(Plan::share(&entry.plan))
+      }
+      None => {
+        self.misses += 1;
+        None
+      }
+    }
+  }
+
+  /// Holds 'plan' for 'key' and returns a plan sharing it. If a plan was held for 'key'
+  /// in the meantime, 'plan' is dropped and the held one is shared instead.
+  pub fn insert(&mut self, key: ~str, plan: Plan) -> Plan {
+    self.clock += 1;
+    let clock = self.clock;
+    match self.plans.find_mut(&key) {
+      Some(entry) => {
+        Memo::touch(&mut self.order, entry, clock);
+        return Plan::share(&entry.plan);
+      }
+      None => (),
+    }
+    let plan = Arc::new(plan);
+    let shared = Plan::share(&plan);
+    self.order.insert(clock, key.clone());
+    self.plans.insert(key, MemoEntry { plan: plan, last_use: clock });
+    self.evict();
+    shared
+  }
+
+  /// Returns the number of plans the memo holds at most.
+  pub fn capacity(&self) -> uint {
+    self.capacity
+  }
+
+  /// Sets the number of plans the memo holds at most, evicting the least recently used
+  /// ones beyond it.
+  pub fn set_capacity(&mut self, capacity: uint) {
+    self.capacity = capacity;
+    self.evict();
+  }
+
+  /// Returns the counters of the memo.
+  pub fn stats(&self) -> MemoStats {
+    MemoStats {
+      hits: self.hits,
+      misses: self.misses,
+      evictions: self.evictions,
+      plans: self.plans.len(),
+    }
+  }
+
+  /// Removes every plan, keeping the counters.
+  pub fn clear(&mut self) {
+    self.plans.clear();
+    self.order.clear();
+  }
+
+  /// Moves the key of 'entry' to the end of 'order', as used at 'clock'.
+  fn touch(order: &mut TrieMap<~str>, entry: &mut MemoEntry, clock: uint) {
+    let key = order.pop(&entry.last_use).unwrap();
+    order.insert(clock, key);
+    entry.last_use = clock;
+  }
+
+  /// Evicts the least recently used plans beyond the capacity.
+  fn evict(&mut self) {
+    while self.plans.len() > self.capacity {
+      let oldest = match self.order.iter().next() {
+        Some((last_use, _)) => last_use,
+        None => break,
+      };
+      let key = self.order.pop(&oldest).unwrap();
+      self.plans.pop(&key);
+      self.evictions += 1;
+    }
+  }
+}
+
+/// Keeps the process memo enabled, see `enable_memo`. Dropping the last guard destroys
+/// the memo.
+pub struct MemoGuard {
+  priv unused: (),
+}
+
+impl Drop for MemoGuard {
+  fn drop(&mut self) {
+    unsafe {
+      let _g = MEMO_LOCK.lock();
+      (*MEMO).guards -= 1;
+      if (*MEMO).guards == 0 {
+        // The plans still used by a transform are destroyed with their last user.
+        let memo: ~Memo = transmute(MEMO);
+        MEMO = 0 as *mut Memo;
+        drop(memo);
+      }
+    }
+  }
+}
+
+/// Returns the process memo, or None while it is disabled. The caller holds MEMO_LOCK.
+unsafe fn memo() -> Option<&'static mut Memo> {
+  if MEMO.is_null() {
+    None
+  } else {
+    Some(&mut *MEMO)
+  }
+}
+
+/// Enables the process memo, holding at most 'capacity' plans, until the returned guard
+/// is dropped. The memo is disabled by default. If it is already enabled, its capacity
+/// is set to 'capacity' and it stays enabled until every guard is dropped.
+pub fn enable_memo(capacity: uint) -> MemoGuard {
+  unsafe {
+    let _g = MEMO_LOCK.lock();
+    match memo() {
+      Some(memo) => memo.set_capacity(capacity),
+      None => MEMO = transmute(~Memo::new(capacity)),
+    }
+    (*MEMO).guards += 1;
+  }
+  MemoGuard { unused: () }
+}
+
+/// Returns the number of plans the memo holds at most, zero while it is disabled.
+pub fn memo_capacity() -> uint {
+  unsafe {
+    let _g = MEMO_LOCK.lock();
+    memo().map_or(0, |memo| memo.capacity())
+  }
+}
+
+/// Returns the counters of the memo, zero while it is disabled.
+pub fn memo_stats() -> MemoStats {
+  unsafe {
+    let _g = MEMO_LOCK.lock();
+    match memo() {
+      Some(memo) => memo.stats(),
+      None => MemoStats { hits: 0, misses: 0, evictions: 0, plans: 0 },
+    }
+  }
+}
+
+/// Removes every plan from the memo, keeping its counters. The plans no transform uses
+/// are destroyed right away, the others along with their last transform.
+pub fn clear_memo() {
+  unsafe {
+    let _g = MEMO_LOCK.lock();
+    match memo() {
+      Some(memo) => memo.clear(),
+      None => (),
+    }
+  }
+}
+
+/// Makes a plan with 'make' through `plan_cached`, unless the memo holds the plan of
+/// 'transform', as returned by `describe`, for arrays aligned like 'input' and 'output'.
+/// The plan returned shares the one of the memo, see `Plan::execute_on`. MEMO_LOCK is
+/// not held while planning, so the plan of another thread made meanwhile is kept.
+pub fn plan_memoized<Tin, Tout>(planner: &Planner, precision: Precision, transform: &str,
+                                input: *mut Tin, output: *mut Tout,
+                                make: |c_uint| -> Option<Plan>) -> Result<Plan, FftwError> {
+  let (in_alignment, out_alignment) = unsafe {
+    (fftw_alignment_of(input as *mut f64), fftw_alignment_of(output as *mut f64))
+  };
+  let key = format!("{} {} {} {} {} {}", precision, transform, planner.fftw_flags(),
+                    planner.threads(), in_alignment, out_alignment);
+  let found = unsafe {
+    let _g = MEMO_LOCK.lock();
+    memo().map(|memo| memo.find(&key))
+  };
+  match found {
+    Some(Some(plan)) => Ok(plan),
+    Some(None) => {
+      let plan = if_ok!(plan_cached(planner, precision, transform,
+                                    |flags| make(flags)));
+      unsafe {
+        let _g = MEMO_LOCK.lock();
+        // The memo may have been destroyed while planning.
+        match memo() {
+          Some(memo) => Ok(memo.insert(key, plan)),
+          None => Ok(plan),
+        }
+      }
+    }
+    None => plan_cached(planner, precision, transform, |flags| make(flags)),
+  }
+}
@@ -0,0 +1,302 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Options of the fftw3 planner, given to the constructors of the transforms.
+//!
+//! Planning with more rigor takes longer, but the resulting plan computes the transform
+//! faster : this is worth it for transforms computed many times. Every level but
+//! `Estimate` computes transforms while planning, so the constructors plan before the
+//! input values are copied in the buffers.
+//!
+//! The constructors plan with `Estimate`. Their `_with` variants take either a `Rigor`,
+//! or a `Planner` setting the other options of the planner as well, such as a
+//! `PlanCache` remembering the planned transforms, or the number of threads computing
+//! the transform with the `fftw3_threads` option. They return `NoPlan` when fftw3 has no
+//! plan satisfying these options.
+//!
+//! ```rust
+//! use fftw3_rust::{Fftw, Planner, Measure, Patient};
+//!
+//! let mut fftw = Fftw::from_slice_with([1f64, 0f64, 2f64, 4f64], Measure).unwrap();
+//! let half = fftw.compute().unwrap().to_owned();
+//! // Plan for at most 100ms, without ever overwriting the input of the transform.
+//! let planner = Planner::new(Patient).with_time_limit(0.1).with_preserved_input();
+//! let mut inv = Fftw::from_slice_c2r_with(half, planner).unwrap();
+//! inv.compute();
+//! ```
+//!
+//! With the `fftw3_threads` option, large transforms are computed faster by several
+//! threads :
+//!
+//! ```ignore
+//! # use fftw3_rust::{Fftw, Planner, Measure};
+//! # use num::complex::Cmplx;
+//! let image = std::vec::from_elem(1024 * 1024, Cmplx::new(0f64, 0f64));
+//! let planner = Planner::new(Measure).with_threads(4);
+//! let mut fftw = Fftw::from_slice_nd_with([1024, 1024], image, planner).unwrap();
+//! ```
+
+use fftw3_bindgen::{FFTW_ESTIMATE, FFTW_MEASURE, FFTW_PATIENT, FFTW_EXHSTIVE,
+                    FFTW_DESTROY_INT, FFTW_PRESERVE_INT, FFTW_CONSERVE_MEMORY,
+                    FFTW_UNALIGNED, FFTW_NO_TIMELIMIT, fftw_set_timelimit,
+                    fftwf_set_timelimit};
+#[cfg(target_arch = "x86_64")]
+use fftw3_bindgen::fftwl_set_timelimit;
+#[cfg(fftw3_threads)]
+use fftw3_bindgen::{fftw_init_threads, fftwf_init_threads, fftw_plan_with_nthreads,
+                    fftwf_plan_with_nthreads};
+#[cfg(target_arch = "x86_64", fftw3_threads)]
+use fftw3_bindgen::{fftwl_init_threads, fftwl_plan_with_nthreads};
+
+use std::libc::{c_uint, c_double};
+#[cfg(fftw3_threads)]
+use std::libc::c_int;
+
+use fftw3_cache::PlanCache;
+#[cfg(fftw3q)]
+use fftw3_quad;
+
+use super::{PRECISIONS, Double, Single, LOCK};
+#[cfg(target_arch = "x86_64")]
+use super::Extended;
+#[cfg(fftw3q)]
+use super::Quadruple;
+
+/// Whether the threads of fftw3 were set up, once tried.
+#[cfg(fftw3_threads)]
+static mut THREADS_READY: Option<bool> = None;
+
+/// How hard the planner looks for a fast way to compute a transform.
+#[deriving(Eq, Clone)]
+pub enum Rigor {
+  /// Picks a plan from heuristics, without computing any transform. The arrays of the
+  /// transform are left untouched.
+  Estimate,
+  /// Times a few candidate plans and keeps the fastest one.
+  Measure,
+  /// Times a wider range of plans than `Measure`, which takes several times longer.
+  Patient,
+  /// Times every plan the planner knows of. Planning can take a very long time.
+  Exhaustive,
+}
+
+impl Rigor {
+  /// Returns the fftw3 flag selecting this rigor.
+  pub fn fftw_flags(&self) -> c_uint {
+    match *self {
+      Estimate => FFTW_ESTIMATE,
+      Measure => FFTW_MEASURE,
+      Patient => FFTW_PATIENT,
+      Exhaustive => FFTW_EXHSTIVE,
+    }
+  }
+}
+
+/// Options of the planner : its rigor, a limit on the planning time, the constraints on
+/// the arrays of the transform, and the cache of the planned transforms.
+#[deriving(Eq, Clone)]
+pub struct Planner {
+  priv rigor: Rigor,
+  priv time_limit: Option<f64>,
+  priv input_flag: c_uint,
+  priv conserve_memory: bool,
+  priv unaligned: bool,
+  priv cache: Option<PlanCache>,
+  priv threads: uint,
+}
+
+impl Planner {
+  /// Creates a planner with the given rigor, no time limit, the default constraints of
+  /// fftw3, and a single thread.
+  pub fn new(rigor: Rigor) -> Planner {
+    Planner {
+      rigor: rigor,
+      time_limit: None,
+      input_flag: 0,
+      conserve_memory: false,
+      unaligned: false,
+      cache: None,
+      threads: 1,
+    }
+  }
+
+  /// Plans with 'rigor', keeping the other options.
+  pub fn with_rigor(self, rigor: Rigor) -> Planner {
+    Planner { rigor: rigor, .. self }
+  }
+
+  /// Stops looking for faster plans after about 'seconds' seconds, and keeps the best
+  /// plan found so far. Planning with `Estimate` is never limited.
+  pub fn with_time_limit(self, seconds: f64) -> Planner {
+    Planner { time_limit: Some(seconds), .. self }
+  }
+
+  /// Only allows plans leaving the input array untouched. This is the default of every
+  /// transform except the complex to real ones. fftw3 can not plan multi-dimensional
+  /// complex to real transforms preserving their input, their constructors then return
+  /// `NoPlan`.
+  pub fn with_preserved_input(self) -> Planner {
+    Planner { input_flag: FFTW_PRESERVE_INT, .. self }
+  }
+
+  /// Allows plans overwriting the input array with arbitrary values, which can be
+  /// faster. This is the default of the complex to real transforms.
+  pub fn with_destroyed_input(self) -> Planner {
+    Planner { input_flag: FFTW_DESTROY_INT, .. self }
+  }
+
+  /// Prefers plans using less memory, even if they are slower.
+  pub fn with_conserved_memory(self) -> Planner {
+    Planner { conserve_memory: true, .. self }
+  }
+
+  /// Allows the plan to be computed on arrays without the alignment of the planned
+  /// ones. The plan can be slower, since it can not use simd instructions.
+  pub fn with_unaligned(self) -> Planner {
+    Planner { unaligned: true, .. self }
+  }
+
+  /// Records the transforms planned with more rigor than `Estimate` in 'cache', and plans
+  /// the ones it already holds from the wisdom only.
+  pub fn with_cache(self, cache: PlanCache) -> Planner {
+    Planner { cache: Some(cache), .. self }
+  }
+
+  /// Plans transforms computed by 'threads' threads. The threads of fftw3 are set up by
+  /// the first such planner, see `init_threads`. 0 threads are taken as 1. Only available
+  /// with the `fftw3_threads` option, the transforms are otherwise computed by a single
+  /// thread.
+  #[cfg(fftw3_threads)]
+  pub fn with_threads(self, threads: uint) -> Planner {
+    Planner { threads: if threads == 0 { 1 } else { threads }, .. self }
+  }
+
+  /// Returns the rigor of this planner.
+  pub fn rigor(&self) -> Rigor {
+    self.rigor
+  }
+
+  /// Returns the time limit of this planner, in seconds.
+  pub fn time_limit(&self) -> Option<f64> {
+    self.time_limit
+  }
+
+  /// Returns the plan cache of this planner.
+  pub fn cache<'a>(&'a self) -> Option<&'a PlanCache> {
+    self.cache.as_ref()
+  }
+
+  /// Returns the number of threads of the transforms of this planner.
+  pub fn threads(&self) -> uint {
+    self.threads
+  }
+
+  /// Returns the fftw3 flags selecting these options.
+  pub fn fftw_flags(&self) -> c_uint {
+    let mut flags = self.rigor.fftw_flags() | self.input_flag;
+    if self.conserve_memory {
+      flags |= FFTW_CONSERVE_MEMORY;
+    }
+    if self.unaligned {
+      flags |= FFTW_UNALIGNED;
+    }
+    flags
+  }
+}
+
+/// Values the constructors of the transforms accept to configure the planner.
+pub trait ToPlanner {
+  /// Returns the options of the planner.
+  fn to_planner(&self) -> Planner;
+}
+
+impl ToPlanner for Rigor {
+  fn to_planner(&self) -> Planner {
+    Planner::new(*self)
+  }
+}
+
+impl ToPlanner for Planner {
+  fn to_planner(&self) -> Planner {
+    self.clone()
+  }
+}
+
+/// Sets up the threads of every library of fftw3, once per process. Returns false if
+/// they could not be set up : every transform is then computed by a single thread.
+/// The threads are run by the pthreads libraries of fftw3, or by its OpenMP ones when
+/// built with the `fftw3_omp` option.
+/// The first planner with more than one thread calls it, calling it at startup only
+/// avoids the delay. Only available with the `fftw3_threads` option.
+#[cfg(fftw3_threads)]
+pub fn init_threads() -> bool {
+  unsafe {
+    let _g = LOCK.lock();
+    threads_ready()
+  }
+}
+
+/// Sets up the threads if it was not tried yet, and returns whether they are ready.
+/// The caller holds the lock.
+#[cfg(fftw3_threads)]
+unsafe fn threads_ready() -> bool {
+  match THREADS_READY {
+    Some(ready) => ready,
+    None => {
+      let ready = PRECISIONS.iter().all(|&precision| match precision {
+        Double => fftw_init_threads() != 0,
+        Single => fftwf_init_threads() != 0,
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_init_threads() != 0,
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::init_threads() != 0,
+      });
+      THREADS_READY = Some(ready);
+      ready
+    }
+  }
+}
+
+/// Sets the time limit and the number of threads of 'planner' and returns its flags,
+/// right before a planner call. Both are global to each library of fftw3, so they are
+/// set in all of them. The caller holds the lock.
+pub unsafe fn prepare(planner: &Planner) -> c_uint {
+  let seconds = match planner.time_limit {
+    Some(seconds) => seconds as c_double,
+    None => FFTW_NO_TIMELIMIT as c_double,
+  };
+  for &precision in PRECISIONS.iter() {
+    match precision {
+      Double => fftw_set_timelimit(seconds),
+      Single => fftwf_set_timelimit(seconds),
+      #[cfg(target_arch = "x86_64")]
+      Extended => fftwl_set_timelimit(seconds),
+      #[cfg(fftw3q)]
+      Quadruple => fftw3_quad::set_timelimit(seconds),
+    }
+  }
+  set_threads(planner);
+  planner.fftw_flags()
+}
+
+/// Sets the number of threads of 'planner' in every library. The caller holds the lock.
+#[cfg(fftw3_threads)]
+unsafe fn set_threads(planner: &Planner) {
+  // Once set up, the threads of a previous planner are used until set again.
+  if (planner.threads > 1 || THREADS_READY.is_some()) && threads_ready() {
+    let threads = planner.threads as c_int;
+    for &precision in PRECISIONS.iter() {
+      match precision {
+        Double => fftw_plan_with_nthreads(threads),
+        Single => fftwf_plan_with_nthreads(threads),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_plan_with_nthreads(threads),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::plan_with_nthreads(threads),
+      }
+    }
+  }
+}
+
+/// Without the threads libraries, every transform is computed by a single thread.
+#[cfg(not(fftw3_threads))]
+unsafe fn set_threads(_: &Planner) {
+}
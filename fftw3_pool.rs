@@ -0,0 +1,200 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Pool of tasks computing many independent transforms.
+//!
+//! An `FftwPool` spreads the complex signals submitted to it over a fixed number of
+//! worker tasks. Each worker keeps one `Fftw` per signal length, so the plan of a length
+//! is only made once per worker, and the spectra come back in the order the signals were
+//! submitted. The signals can have any length.
+//!
+//! ```rust
+//! use fftw3_rust::{FftwPool, Measure};
+//! use num::complex::Cmplx;
+//!
+//! let mut pool: FftwPool<f64> = FftwPool::new_with(4, Measure);
+//! for n in range(1u, 100u) {
+//!   pool.submit(std::vec::from_elem(n % 16 + 1, Cmplx::new(1f64, 0f64)));
+//! }
+//! for spectrum in pool {
+//!   println!("{}", spectrum.unwrap()[0]);
+//! }
+//! ```
+
+use num::complex::Cmplx;
+
+use std::hashmap::{HashMap, HashSet};
+use std::vec;
+
+use super::{Fftw, FftBuf, FftwError, SizeMismatch, FftwFloat};
+use fftw3_planner::{Estimate, Planner, ToPlanner};
+
+type Spectrum<F> = Result<~[Cmplx<F>], FftwError>;
+
+/// A fixed number of tasks computing the transforms of the submitted signals.
+pub struct FftwPool<F> {
+  priv workers: ~[Sender<Option<(uint, ~[Cmplx<F>])>>],
+  // Number of signals submitted to each worker whose spectrum was not received yet.
+  priv pending: ~[uint],
+  priv results: Receiver<(uint, uint, Spectrum<F>)>,
+  // Spectra received before the ones of the signals submitted earlier.
+  priv received: HashMap<uint, Spectrum<F>>,
+  // Signals whose spectrum was returned by `compute_all`, skipped by the iterator.
+  priv taken: HashSet<uint>,
+  priv submitted: uint,
+  priv returned: uint,
+}
+
+impl<F: FftwFloat + Send> FftwPool<F> {
+  /// Starts 'workers' tasks computing forward transforms.
+  pub fn new(workers: uint) -> FftwPool<F> {
+    FftwPool::new_with(workers, Estimate)
+  }
+
+  /// Same as `new`, planned with the options of 'planner'.
+  pub fn new_with<P: ToPlanner>(workers: uint, planner: P) -> FftwPool<F> {
+    FftwPool::start(workers, planner.to_planner(), false)
+  }
+
+  /// Starts 'workers' tasks computing inverse transforms.
+  pub fn new_inv(workers: uint) -> FftwPool<F> {
+    FftwPool::new_inv_with(workers, Estimate)
+  }
+
+  /// Same as `new_inv`, planned with the options of 'planner'.
+  pub fn new_inv_with<P: ToPlanner>(workers: uint, planner: P) -> FftwPool<F> {
+    FftwPool::start(workers, planner.to_planner(), true)
+  }
+
+  fn start(workers: uint, planner: Planner, inverse: bool) -> FftwPool<F> {
+    assert!(workers > 0, "a pool needs at least one worker");
+    let (results_tx, results_rx) = channel();
+    let senders = range(0, workers).map(|worker| {
+      let (tx, rx) = channel();
+      let (planner, results_tx) = (planner.clone(), results_tx.clone());
+      spawn(proc() {
+        work(worker, planner, inverse, rx, results_tx);
+      });
+      tx
+    }).collect();
+    FftwPool {
+      workers: senders,
+      pending: vec::from_elem(workers, 0u),
+      results: results_rx,
+      received: HashMap::new(),
+      taken: HashSet::new(),
+      submitted: 0,
+      returned: 0,
+    }
+  }
+
+  /// Queues the transform of 'signal' on the worker with the fewest pending signals.
+  pub fn submit(&mut self, signal: ~[Cmplx<F>]) {
+    let mut worker = 0;
+    for (i, &pending) in self.pending.iter().enumerate() {
+      if pending < self.pending[worker] {
+        worker = i;
+      }
+    }
+    self.pending[worker] += 1;
+    self.workers[worker].send(Some((self.submitted, signal)));
+    self.submitted += 1;
+  }
+
+  /// Returns the number of signals whose spectrum was not returned yet.
+  pub fn pending(&self) -> uint {
+    self.submitted - self.returned - self.taken.len()
+  }
+
+  /// Computes the transforms of all the 'signals', and returns their spectra in the same
+  /// order. The spectra of the signals submitted before are still returned by the
+  /// iterator.
+  pub fn compute_all(&mut self, signals: ~[~[Cmplx<F>]])
+                     -> ~[Result<~[Cmplx<F>], FftwError>] {
+    let first = self.submitted;
+    for signal in signals.move_iter() {
+      self.submit(signal);
+    }
+    range(first, self.submitted).map(|index| {
+      self.taken.insert(index);
+      self.receive(index)
+    }).collect()
+  }
+
+  /// Waits for the spectrum of the signal submitted at 'index'.
+  fn receive(&mut self, index: uint) -> Spectrum<F> {
+    while !self.received.contains_key(&index) {
+      let (worker, index, spectrum) = self.results.recv();
+      self.pending[worker] -= 1;
+      self.received.insert(index, spectrum);
+    }
+    self.received.pop(&index).unwrap()
+  }
+}
+
+impl<F: FftwFloat + Send> Iterator<Result<~[Cmplx<F>], FftwError>> for FftwPool<F> {
+  /// Waits for the spectrum of the next signal, in submission order. Returns None once
+  /// the spectra of all the submitted signals were returned. The error of a signal is
+  /// the one of the `Fftw` constructors, for instance for an empty signal.
+  fn next(&mut self) -> Option<Result<~[Cmplx<F>], FftwError>> {
+    while self.returned < self.submitted && self.taken.remove(&self.returned) {
+      self.returned += 1;
+    }
+    if self.returned == self.submitted {
+      return None;
+    }
+    let next = self.returned;
+    self.returned += 1;
+    Some(self.receive(next))
+  }
+}
+
+#[unsafe_destructor]
+impl<F: Send> Drop for FftwPool<F> {
+  fn drop(&mut self) {
+    // The workers stop once they computed the signals already submitted.
+    for worker in self.workers.iter() {
+      worker.try_send(None);
+    }
+  }
+}
+
+/// Computes the transforms of the signals received on 'jobs' until the pool stops it,
+/// with one `Fftw` per signal length. A signal that cannot be transformed gets an error,
+/// so that the pool never waits for its spectrum.
+fn work<F: FftwFloat + Send>(worker: uint, planner: Planner, inverse: bool,
+                             jobs: Receiver<Option<(uint, ~[Cmplx<F>])>>,
+                             results: Sender<(uint, uint, Spectrum<F>)>) {
+  let mut plans: HashMap<uint, Fftw<FftBuf<Cmplx<F>>, FftBuf<Cmplx<F>>>> = HashMap::new();
+  loop {
+    let (index, signal) = match jobs.recv() {
+      Some(job) => job,
+      None => return,
+    };
+    let n = signal.len();
+    if !plans.contains_key(&n) {
+      let fftw = if inverse {
+        Fftw::new_nd_inv_with([n], planner.clone())
+      } else {
+        Fftw::new_nd_with([n], planner.clone())
+      };
+      match fftw {
+        Ok(fftw) => {
+          plans.insert(n, fftw);
+        }
+        Err(err) => {
+          results.try_send((worker, index, Err(err)));
+          continue;
+        }
+      }
+    }
+    let fftw = plans.get_mut(&n);
+    fftw.ref_input().clear();
+    fftw.ref_input().push_slice(signal);
+    let filled = fftw.ref_input().len();
+    let spectrum = match fftw.compute() {
+      Some(spectrum) => Ok(spectrum.to_owned()),
+      None => Err(SizeMismatch(n, filled)),
+    };
+    // The pool may have been dropped without waiting for the spectra.
+    results.try_send((worker, index, spectrum));
+  }
+}
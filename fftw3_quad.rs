@@ -0,0 +1,379 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Quadruple precision transforms, computed by the __float128 version of fftw3.
+//!
+//! This module is only built with `--cfg fftw3q`, since it links with libfftw3q and
+//! libquadmath. As for `LongDouble`, Rust has no arithmetic on `Quad` : the values are
+//! converted from `f64` before the transform and back to `f64` after it.
+//!
+//! ```rust
+//! use fftw3_rust::{Fftw, Quad};
+//!
+//! let input: ~[Quad] = [1f64, 0f64, 2f64, 4f64].iter().map(|&x| Quad::from_f64(x)).collect();
+//! let mut fftw = Fftw::from_slice(input);
+//! for cx in fftw.compute().unwrap().iter() {
+//!   println!("{} {}", cx.re.to_f64(), cx.im.to_f64());
+//! }
+//! ```
+
+use num::complex::Cmplx;
+
+use fftw3_bindgen::{__float128, fftwq_complex, fftw_plan, fftw_iodim64, fftw_r2r_kind,
+                    fftwq_alloc_real, fftwq_alloc_complex, fftwq_free, fftwq_plan_dft,
+                    fftwq_plan_dft_r2c, fftwq_plan_dft_c2r, fftwq_plan_many_dft,
+                    fftwq_plan_many_dft_r2c, fftwq_plan_many_dft_c2r, fftwq_plan_r2r,
+                    fftwq_plan_guru64_dft, fftwq_plan_guru64_dft_r2c,
+                    fftwq_plan_guru64_dft_c2r, fftwq_plan_guru64_r2r, fftwq_execute,
+                    fftwq_destroy_plan, fftwq_set_timelimit, fftwq_execute_dft,
+                    fftwq_execute_dft_r2c, fftwq_execute_dft_c2r, fftwq_execute_r2r,
+                    fftwq_forget_wisdom, fftwq_export_wisdom_to_filename,
+                    fftwq_export_wisdom_to_string, fftwq_import_wisdom_from_filename,
+                    fftwq_import_wisdom_from_string, fftwq_import_system_wisdom,
+                    fftwq_version};
+#[cfg(fftw3_threads)]
+use fftw3_bindgen::{fftwq_init_threads, fftwq_plan_with_nthreads};
+
+use std::cast::transmute;
+use std::fmt::{Show, Formatter};
+use std::fmt;
+use std::libc::{c_int, c_uint, c_void, c_double, c_char, size_t};
+
+use fftw3_convert::{FRAC_MASK, F64_INFINITY, round_to_f64};
+
+use super::{TransformData, Transform, FftwFloat, Plan, Precision, Quadruple, plan_dft_r2c,
+            plan_dft_c2r, Planner, FftwError};
+
+/// Mask of the bits of the fraction stored in the high word of a __float128.
+static HIGH_FRAC_MASK: u64 = (1 << 48) - 1;
+
+/// A value of the IEEE 754 quadruple precision type, stored like a __float128.
+#[deriving(Clone)]
+pub struct Quad {
+  priv lo: u64,
+  priv hi: u64,
+}
+
+impl Quad {
+  /// Converts a f64 to quadruple precision. Every f64 value is represented exactly.
+  pub fn from_f64(x: f64) -> Quad {
+    let bits: u64 = unsafe { transmute(x) };
+    let sign = bits & (1 << 63);
+    let exp = (bits >> 52) & 0x7ff;
+    let frac = bits & FRAC_MASK;
+    let (exp, frac) = if exp == 0x7ff {
+      (0x7fff, frac)
+    } else if exp != 0 {
+      (exp + 16383 - 1023, frac)
+    } else if frac == 0 {
+      (0, 0)
+    } else {
+      // Subnormal values are normalized, the quad exponent is large enough.
+      let (mut exp, mut mantissa) = (16383 - 1022, frac);
+      while mantissa & (1 << 52) == 0 {
+        mantissa <<= 1;
+        exp -= 1;
+      }
+      (exp, mantissa & FRAC_MASK)
+    };
+
+    // The 52 bits of the fraction are the highest of the 112 bits of the quad one.
+    Quad {
+      lo: frac << 60,
+      hi: sign | exp << 48 | frac >> 4,
+    }
+  }
+
+  /// Converts this value to a f64, rounding to the nearest (ties to even). Values too
+  /// large for a f64 become infinite, and values too small become zero.
+  pub fn to_f64(&self) -> f64 {
+    let sign = self.hi & (1 << 63);
+    let exp = ((self.hi >> 48) & 0x7fff) as int;
+    let high = self.hi & HIGH_FRAC_MASK;
+    let bits = if exp == 0x7fff {
+      if high == 0 && self.lo == 0 {
+        F64_INFINITY
+      } else {
+        F64_INFINITY | 1 << 51 | (high << 4 | self.lo >> 60) & FRAC_MASK
+      }
+    } else if exp == 0 {
+      // Zero, or subnormals far below the range of f64.
+      0
+    } else {
+      // The 64 highest bits of the 113 bits of the mantissa, the others only matter to
+      // break ties.
+      let m = 1 << 63 | high << 15 | self.lo >> 49;
+      round_to_f64(exp - 16383, m, self.lo & ((1 << 49) - 1) != 0)
+    };
+    unsafe { transmute(sign | bits) }
+  }
+}
+
+impl Show for Quad {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f.buf, "{}", self.to_f64())
+  }
+}
+
+/// Negation only flips the sign bit, which is exact.
+impl Neg<Quad> for Quad {
+  fn neg(&self) -> Quad {
+    Quad {
+      lo: self.lo,
+      hi: self.hi ^ 1 << 63,
+    }
+  }
+}
+
+impl fmt::Float for Quad {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    fmt::Float::fmt(&self.to_f64(), f)
+  }
+}
+
+/// Converts a complex value to quadruple precision.
+pub fn cmplx_to_quad(cx: &Cmplx<f64>) -> Cmplx<Quad> {
+  Cmplx {
+    re: Quad::from_f64(cx.re),
+    im: Quad::from_f64(cx.im),
+  }
+}
+
+/// Converts a complex value from quadruple precision, rounding each part to the
+/// nearest f64.
+pub fn quad_to_cmplx(cx: &Cmplx<Quad>) -> Cmplx<f64> {
+  Cmplx::new(cx.re.to_f64(), cx.im.to_f64())
+}
+
+/// Computes a quadruple precision plan on the arrays it was made for.
+pub unsafe fn execute(plan: fftw_plan) {
+  fftwq_execute(plan);
+}
+
+/// Destroys a quadruple precision plan. The caller holds the lock.
+pub unsafe fn destroy_plan(plan: fftw_plan) {
+  fftwq_destroy_plan(plan);
+}
+
+/// Sets the time limit of the quadruple precision planner. The caller holds the lock.
+pub unsafe fn set_timelimit(seconds: c_double) {
+  fftwq_set_timelimit(seconds);
+}
+
+/// Sets up the threads of the quadruple precision library. The caller holds the lock.
+#[cfg(fftw3_threads)]
+pub unsafe fn init_threads() -> c_int {
+  fftwq_init_threads()
+}
+
+/// Sets the number of threads of the next quadruple precision plans. The caller holds the
+/// lock.
+#[cfg(fftw3_threads)]
+pub unsafe fn plan_with_nthreads(threads: c_int) {
+  fftwq_plan_with_nthreads(threads);
+}
+
+/// Writes the quadruple precision wisdom to a file. The caller holds the lock.
+pub unsafe fn export_wisdom_to_filename(filename: *c_char) -> c_int {
+  fftwq_export_wisdom_to_filename(filename)
+}
+
+/// Returns the quadruple precision wisdom as a string allocated by the library, freed
+/// with `free_wisdom_string`. The caller holds the lock.
+pub unsafe fn export_wisdom_to_string() -> *mut c_char {
+  fftwq_export_wisdom_to_string()
+}
+
+/// Frees a string returned by `export_wisdom_to_string`.
+pub unsafe fn free_wisdom_string(wisdom: *mut c_char) {
+  fftwq_free(wisdom as *mut c_void);
+}
+
+/// Adds the wisdom stored in a file to the quadruple precision one. The caller holds the
+/// lock.
+pub unsafe fn import_wisdom_from_filename(filename: *c_char) -> c_int {
+  fftwq_import_wisdom_from_filename(filename)
+}
+
+/// Adds the wisdom held by a string to the quadruple precision one. The caller holds the
+/// lock.
+pub unsafe fn import_wisdom_from_string(input: *c_char) -> c_int {
+  fftwq_import_wisdom_from_string(input)
+}
+
+/// Adds the system wisdom to the quadruple precision one. The caller holds the lock.
+pub unsafe fn import_system_wisdom() -> c_int {
+  fftwq_import_system_wisdom()
+}
+
+/// Returns the version string of the quadruple precision library.
+pub fn version() -> *c_char {
+  unsafe { fftwq_version.as_ptr() }
+}
+
+/// Drops the quadruple precision wisdom. The caller holds the lock.
+pub unsafe fn forget_wisdom() {
+  fftwq_forget_wisdom();
+}
+
+/// Computes a quadruple precision plan on other arrays than the ones it was made for.
+pub unsafe fn execute_with(plan: fftw_plan, input: *mut c_void, output: *mut c_void,
+                           in_cmplx: bool, out_cmplx: bool) {
+  match (in_cmplx, out_cmplx) {
+    (true, true) =>
+      fftwq_execute_dft(plan, input as *mut fftwq_complex, output as *mut fftwq_complex),
+    (false, true) =>
+      fftwq_execute_dft_r2c(plan, input as *mut __float128, output as *mut fftwq_complex),
+    (true, false) =>
+      fftwq_execute_dft_c2r(plan, input as *mut fftwq_complex, output as *mut __float128),
+    (false, false) =>
+      fftwq_execute_r2r(plan, input as *mut __float128, output as *mut __float128),
+  }
+}
+
+impl TransformData for Quad {
+  #[inline]
+  fn fftw_alloc(capacity: uint) -> *mut Quad {
+    unsafe {
+      fftwq_alloc_real(capacity as size_t) as *mut Quad
+    }
+  }
+
+  #[inline]
+  fn fftw_free(data: *mut Quad) {
+    unsafe {
+      fftwq_free(data as *mut c_void);
+    }
+  }
+
+  #[inline]
+  fn transform_size(input_capacity: uint, _: &[Quad]) -> uint {
+    input_capacity/2 + 1
+  }
+
+  #[inline]
+  fn is_complex(_: &[Quad]) -> bool {
+    false
+  }
+
+  #[inline]
+  fn precision(_: &[Quad]) -> Precision {
+    Quadruple
+  }
+}
+
+impl Transform<Cmplx<Quad>> for Quad {
+  #[inline]
+  fn plan(N: uint, input: *mut Quad, output: *mut Cmplx<Quad>,
+          planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft_r2c([N], input, output, planner)
+  }
+
+  #[inline]
+  fn plan_inv(N: uint, input: *mut Cmplx<Quad>, output: *mut Quad,
+              planner: &Planner) -> Result<Plan, FftwError> {
+    plan_dft_c2r([N], input, output, planner)
+  }
+}
+
+impl FftwFloat for Quad {
+  #[inline]
+  unsafe fn alloc_complex(n: uint) -> *mut Cmplx<Quad> {
+    fftwq_alloc_complex(n as size_t) as *mut Cmplx<Quad>
+  }
+
+  #[inline]
+  unsafe fn plan_dft(rank: c_int, n: *c_int, input: *mut Cmplx<Quad>,
+                     output: *mut Cmplx<Quad>, sign: c_int,
+                     flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_dft(rank, n, input as *mut fftwq_complex,
+                             output as *mut fftwq_complex, sign, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_dft_r2c(rank: c_int, n: *c_int, input: *mut Quad,
+                         output: *mut Cmplx<Quad>, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_dft_r2c(rank, n, input as *mut __float128,
+                                 output as *mut fftwq_complex, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_dft_c2r(rank: c_int, n: *c_int, input: *mut Cmplx<Quad>,
+                         output: *mut Quad, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_dft_c2r(rank, n, input as *mut fftwq_complex,
+                                 output as *mut __float128, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft(rank: c_int, n: *c_int, howmany: c_int, input: *mut Cmplx<Quad>,
+                          inembed: *c_int, istride: c_int, idist: c_int,
+                          output: *mut Cmplx<Quad>, onembed: *c_int, ostride: c_int,
+                          odist: c_int, sign: c_int, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_many_dft(rank, n, howmany, input as *mut fftwq_complex, inembed,
+                                  istride, idist, output as *mut fftwq_complex, onembed,
+                                  ostride, odist, sign, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft_r2c(rank: c_int, n: *c_int, howmany: c_int, input: *mut Quad,
+                              inembed: *c_int, istride: c_int, idist: c_int,
+                              output: *mut Cmplx<Quad>, onembed: *c_int, ostride: c_int,
+                              odist: c_int, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_many_dft_r2c(rank, n, howmany, input as *mut __float128, inembed,
+                                      istride, idist, output as *mut fftwq_complex,
+                                      onembed, ostride, odist, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_many_dft_c2r(rank: c_int, n: *c_int, howmany: c_int,
+                              input: *mut Cmplx<Quad>, inembed: *c_int, istride: c_int,
+                              idist: c_int, output: *mut Quad, onembed: *c_int,
+                              ostride: c_int, odist: c_int, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_many_dft_c2r(rank, n, howmany, input as *mut fftwq_complex,
+                                      inembed, istride, idist, output as *mut __float128,
+                                      onembed, ostride, odist, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_r2r(rank: c_int, n: *c_int, input: *mut Quad, output: *mut Quad,
+                     kind: *fftw_r2r_kind, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_r2r(rank, n, input as *mut __float128, output as *mut __float128,
+                             kind, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut Cmplx<Quad>,
+                            output: *mut Cmplx<Quad>, sign: c_int,
+                            flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_guru64_dft(rank, dims, howmany_rank, howmany_dims,
+                                    input as *mut fftwq_complex,
+                                    output as *mut fftwq_complex, sign, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft_r2c(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64, input: *mut Quad,
+                                output: *mut Cmplx<Quad>, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_guru64_dft_r2c(rank, dims, howmany_rank, howmany_dims,
+                                        input as *mut __float128,
+                                        output as *mut fftwq_complex, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_dft_c2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                                howmany_dims: *fftw_iodim64, input: *mut Cmplx<Quad>,
+                                output: *mut Quad, flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_guru64_dft_c2r(rank, dims, howmany_rank, howmany_dims,
+                                        input as *mut fftwq_complex,
+                                        output as *mut __float128, flags), Quadruple)
+  }
+
+  #[inline]
+  unsafe fn plan_guru64_r2r(rank: c_int, dims: *fftw_iodim64, howmany_rank: c_int,
+                            howmany_dims: *fftw_iodim64, input: *mut Quad,
+                            output: *mut Quad, kind: *fftw_r2r_kind,
+                            flags: c_uint) -> Option<Plan> {
+    Plan::new(fftwq_plan_guru64_r2r(rank, dims, howmany_rank, howmany_dims,
+                                    input as *mut __float128, output as *mut __float128,
+                                    kind, flags), Quadruple)
+  }
+}
@@ -0,0 +1,308 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Real to real transforms.
+//!
+//! These transforms are unnormalized : computing a transform followed by its inverse
+//! multiplies the values by the scale factor of each dimension, as given by
+//! `R2rKind::scale`.
+//!
+//! The R2HC transform computes the same values as a real to complex transform, stored
+//! in the "halfcomplex" format : r0, r1, ..., r(n/2), i((n+1)/2-1), ..., i2, i1. The
+//! `halfcomplex_to_cmplx` and `cmplx_to_halfcomplex` functions convert between this
+//! format and the n/2+1 complex values of the first half of the Hermitian symmetry.
+
+use num::complex::Cmplx;
+
+use fftw3_bindgen::{FFTW_R2HC, FFTW_HC2R, FFTW_DHT,
+                    FFTW_REDFT00, FFTW_REDFT01, FFTW_REDFT10, FFTW_REDFT11,
+                    FFTW_RODFT00, FFTW_RODFT01, FFTW_RODFT10, FFTW_RODFT11,
+                    fftw_r2r_kind};
+
+use std::libc::c_int;
+
+use super::{Fftw, FftBuf, FftwError, FftwFloat, TransformBuf, SizeMismatch, UnsupportedSize,
+            Estimate, Planner, ToPlanner, check_shape, precision_of, Plan};
+use fftw3_cache::{plan_cached, describe_layout};
+
+/// Kind of a real to real transform, along one dimension.
+#[deriving(Eq, Clone)]
+pub enum R2rKind {
+  /// DCT-I (REDFT00), for data even around j=0 and even around j=n-1.
+  /// Its scale factor is 2(n-1), it is its own inverse, and it needs n > 1.
+  Dct1,
+  /// DCT-II (REDFT10), the "DCT", for data even around j=-0.5 and even around j=n-0.5.
+  /// Its scale factor is 2n, its inverse is the DCT-III.
+  Dct2,
+  /// DCT-III (REDFT01), the "IDCT", for data even around j=0 and odd around j=n.
+  /// Its scale factor is 2n, its inverse is the DCT-II.
+  Dct3,
+  /// DCT-IV (REDFT11), for data even around j=-0.5 and odd around j=n-0.5.
+  /// Its scale factor is 2n, it is its own inverse.
+  Dct4,
+  /// DST-I (RODFT00), for data odd around j=-1 and odd around j=n.
+  /// Its scale factor is 2(n+1), it is its own inverse.
+  Dst1,
+  /// DST-II (RODFT10), for data odd around j=-0.5 and odd around j=n-0.5.
+  /// Its scale factor is 2n, its inverse is the DST-III.
+  Dst2,
+  /// DST-III (RODFT01), for data odd around j=-1 and even around j=n-1.
+  /// Its scale factor is 2n, its inverse is the DST-II.
+  Dst3,
+  /// DST-IV (RODFT11), for data odd around j=-0.5 and even around j=n-0.5.
+  /// Its scale factor is 2n, it is its own inverse.
+  Dst4,
+  /// Real to halfcomplex transform, computing the same values as a real to complex
+  /// transform. Its scale factor is n, its inverse is the HC2R transform.
+  R2hc,
+  /// Halfcomplex to real transform, computing the same values as a complex to real
+  /// transform. Its scale factor is n, its inverse is the R2HC transform.
+  Hc2r,
+  /// Discrete Hartley transform. Its scale factor is n, it is its own inverse.
+  Dht,
+}
+
+impl R2rKind {
+  /// Returns the kind of the transform undoing this one, up to the scale factor.
+  pub fn inverse(&self) -> R2rKind {
+    match *self {
+      Dct1 => Dct1,
+      Dct2 => Dct3,
+      Dct3 => Dct2,
+      Dct4 => Dct4,
+      Dst1 => Dst1,
+      Dst2 => Dst3,
+      Dst3 => Dst2,
+      Dst4 => Dst4,
+      R2hc => Hc2r,
+      Hc2r => R2hc,
+      Dht => Dht,
+    }
+  }
+
+  /// Returns the factor by which the values are multiplied when computing this
+  /// transform followed by its inverse, along a dimension of 'n' values.
+  pub fn scale(&self, n: uint) -> f64 {
+    match *self {
+      Dct1 => 2f64 * (n as f64 - 1f64),
+      Dst1 => 2f64 * (n as f64 + 1f64),
+      Dct2 | Dct3 | Dct4 | Dst2 | Dst3 | Dst4 => 2f64 * n as f64,
+      R2hc | Hc2r | Dht => n as f64,
+    }
+  }
+
+  /// Returns the smallest number of values this transform supports.
+  fn min_size(&self) -> uint {
+    match *self {
+      Dct1 => 2,
+      _ => 1,
+    }
+  }
+
+  /// Returns the fftw3 constant identifying this kind of transform.
+  pub fn fftw_kind(&self) -> fftw_r2r_kind {
+    match *self {
+      Dct1 => FFTW_REDFT00,
+      Dct2 => FFTW_REDFT10,
+      Dct3 => FFTW_REDFT01,
+      Dct4 => FFTW_REDFT11,
+      Dst1 => FFTW_RODFT00,
+      Dst2 => FFTW_RODFT10,
+      Dst3 => FFTW_RODFT01,
+      Dst4 => FFTW_RODFT11,
+      R2hc => FFTW_R2HC,
+      Hc2r => FFTW_HC2R,
+      Dht => FFTW_DHT,
+    }
+  }
+}
+
+impl<F: FftwFloat> Fftw<FftBuf<F>, FftBuf<F>> {
+  /// Prepare a new real to real transform of the given kind for 'capacity' elements.
+  /// Returns an error if the kind does not support this number of elements.
+  /// The transform can only be computed once the input buffer is full.
+  #[inline]
+  pub fn new_r2r(capacity: uint, kind: R2rKind)
+                 -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_r2r_with(capacity, kind, Estimate)
+  }
+
+  /// Same as `new_r2r`, planned with the options of 'planner'.
+  #[inline]
+  pub fn new_r2r_with<P: ToPlanner>(capacity: uint, kind: R2rKind, planner: P)
+                                    -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_r2r_nd_with([capacity], kind, planner)
+  }
+
+  /// Prepare a new real to real transform from the given slice of numbers.
+  /// Returns an error if the kind does not support this number of elements.
+  /// The elements of the slice are copied in an internal buffer allocated by fftw3, once
+  /// the transform is planned.
+  pub fn from_slice_r2r(slice: &[F], kind: R2rKind)
+                        -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::from_slice_r2r_with(slice, kind, Estimate)
+  }
+
+  /// Same as `from_slice_r2r`, planned with the options of 'planner'.
+  pub fn from_slice_r2r_with<P: ToPlanner>(slice: &[F], kind: R2rKind, planner: P)
+      -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    let mut new = if_ok!(Fftw::new_r2r_with(slice.len(), kind, planner));
+    new.in_data.push_slice(slice);
+    Ok(new)
+  }
+
+  /// Prepare a new discrete Hartley transform for 'capacity' elements.
+  /// Returns an error if 'capacity' is zero.
+  /// The transform can only be computed once the input buffer is full.
+  #[inline]
+  pub fn new_dht(capacity: uint) -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_dht_with(capacity, Estimate)
+  }
+
+  /// Same as `new_dht`, planned with the options of 'planner'.
+  #[inline]
+  pub fn new_dht_with<P: ToPlanner>(capacity: uint, planner: P)
+                                    -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_r2r_with(capacity, Dht, planner)
+  }
+
+  /// Prepare a new real to halfcomplex transform for 'capacity' elements.
+  /// Returns an error if 'capacity' is zero.
+  /// The transform can only be computed once the input buffer is full.
+  #[inline]
+  pub fn new_r2hc(capacity: uint) -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_r2hc_with(capacity, Estimate)
+  }
+
+  /// Same as `new_r2hc`, planned with the options of 'planner'.
+  #[inline]
+  pub fn new_r2hc_with<P: ToPlanner>(capacity: uint, planner: P)
+                                     -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_r2r_with(capacity, R2hc, planner)
+  }
+
+  /// Prepare a new halfcomplex to real transform for 'capacity' elements.
+  /// Returns an error if 'capacity' is zero.
+  /// The transform can only be computed once the input buffer is full.
+  #[inline]
+  pub fn new_hc2r(capacity: uint) -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_hc2r_with(capacity, Estimate)
+  }
+
+  /// Same as `new_hc2r`, planned with the options of 'planner'.
+  #[inline]
+  pub fn new_hc2r_with<P: ToPlanner>(capacity: uint, planner: P)
+                                     -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_r2r_with(capacity, Hc2r, planner)
+  }
+
+  /// Prepare a new real to real transform of the given kind along every dimension of an
+  /// array of arbitrary rank, stored in row-major order. The scale factor of the
+  /// transform is the product of the scale factors of each dimension.
+  /// Returns an error if 'shape' is empty, or if the kind does not support one of its
+  /// dimensions.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_r2r_nd(shape: &[uint], kind: R2rKind)
+                    -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_r2r_nd_with(shape, kind, Estimate)
+  }
+
+  /// Same as `new_r2r_nd`, planned with the options of 'planner'.
+  pub fn new_r2r_nd_with<P: ToPlanner>(shape: &[uint], kind: R2rKind, planner: P)
+                                       -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    let kinds = ::std::vec::from_elem(shape.len(), kind);
+    Fftw::new_r2r_mixed_with(shape, kinds, planner)
+  }
+
+  /// Prepare a new real to real transform over an array of arbitrary rank, stored in
+  /// row-major order, with one kind of transform per dimension. This allows mixing
+  /// cosine and sine transforms along different axes.
+  /// Returns an error if 'shape' is empty, if there is not exactly one kind per
+  /// dimension, or if a kind does not support the size of its dimension.
+  /// The transform can only be computed once the input buffer is full.
+  pub fn new_r2r_mixed(shape: &[uint], kinds: &[R2rKind])
+                       -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    Fftw::new_r2r_mixed_with(shape, kinds, Estimate)
+  }
+
+  /// Same as `new_r2r_mixed`, planned with the options of 'planner'.
+  pub fn new_r2r_mixed_with<P: ToPlanner>(shape: &[uint], kinds: &[R2rKind], planner: P)
+      -> Result<Fftw<FftBuf<F>, FftBuf<F>>, FftwError> {
+    let capacity = if_ok!(check_shape(shape));
+    if kinds.len() != shape.len() {
+      return Err(SizeMismatch(shape.len(), kinds.len()));
+    }
+    for (axis, (&n, kind)) in shape.iter().zip(kinds.iter()).enumerate() {
+      if n < kind.min_size() {
+        return Err(UnsupportedSize(axis));
+      }
+    }
+
+    let _in: FftBuf<F> = TransformBuf::new(capacity);
+    let _out: FftBuf<F> = TransformBuf::new(capacity);
+    let _p = if_ok!(plan_r2r(shape, kinds, _in.data, _out.data, &planner.to_planner()));
+    Ok(Fftw {
+      in_data: _in,
+      out_data: _out,
+      plan: _p,
+      shape: shape.to_owned(),
+      offsets: (0, 0),
+      rows: None,
+    })
+  }
+}
+
+/// Creates a real to real plan over an array of the given shape, stored in row-major
+/// order, with one kind of transform per dimension.
+fn plan_r2r<F: FftwFloat>(shape: &[uint], kinds: &[R2rKind], input: *mut F,
+                          output: *mut F, planner: &Planner) -> Result<Plan, FftwError> {
+  let dims: ~[c_int] = shape.iter().map(|&n| n as c_int).collect();
+  let fftw_kinds: ~[fftw_r2r_kind] = kinds.iter().map(|k| k.fftw_kind()).collect();
+  let transform = describe_layout("r2r", shape, describe_kinds(kinds),
+                                  input as uint == output as uint);
+  plan_cached(planner, precision_of::<F>(), transform, |flags| unsafe {
+    FftwFloat::plan_r2r(dims.len() as c_int, dims.as_ptr(), input, output,
+                        fftw_kinds.as_ptr(), flags)
+  })
+}
+
+/// Returns the kinds of a real to real transform, identifying it in a plan cache.
+pub fn describe_kinds(kinds: &[R2rKind]) -> ~str {
+  let kinds: ~[~str] = kinds.iter().map(|k| k.fftw_kind().to_str()).collect();
+  format!("kinds {}", kinds.connect(","))
+}
+
+/// Converts an array in halfcomplex format to the n/2+1 complex values of the first half
+/// of the Hermitian symmetry, as computed by a real to complex transform. An empty array
+/// has no values.
+pub fn halfcomplex_to_cmplx(hc: &[f64]) -> ~[Cmplx<f64>] {
+  let n = hc.len();
+  range(0, half_len(n)).map(|k| {
+    if k > 0 && k < n - k {
+      Cmplx::new(hc[k], hc[n - k])
+    } else {
+      Cmplx::new(hc[k], 0f64)
+    }
+  }).collect()
+}
+
+/// Converts the n/2+1 complex values of the first half of a Hermitian symmetry to an
+/// array of 'n' values in halfcomplex format. The imaginary parts of the values at
+/// 0 and n/2 (for an even 'n') are dropped, they are zero for a Hermitian array.
+/// Returns an error if there are not n/2+1 complex values, or none for a zero 'n'.
+pub fn cmplx_to_halfcomplex(half: &[Cmplx<f64>], n: uint) -> Result<~[f64], FftwError> {
+  if half.len() != half_len(n) {
+    return Err(SizeMismatch(half_len(n), half.len()));
+  }
+  Ok(range(0, n).map(|k| {
+    if k <= n/2 {
+      half[k].re
+    } else {
+      half[n - k].im
+    }
+  }).collect())
+}
+
+/// Returns the number of complex values of the first half of a Hermitian array of 'n'
+/// values.
+fn half_len(n: uint) -> uint {
+  if n == 0 { 0 } else { n/2 + 1 }
+}
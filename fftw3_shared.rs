@@ -0,0 +1,81 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Plans computed by several tasks at once.
+//!
+//! Only the planner of fftw3 needs to be serialized : a plan can compute its transform on
+//! other arrays than the ones it was made for from several threads at once.
+//! `Fftw::into_shared` keeps the plan of a transform without its buffers, as an immutable
+//! `SharedPlan`. It is Send and Share, so tasks can hold it through an `Arc` and compute
+//! it on their own arrays, without taking any lock.
+//!
+//! ```rust
+//! use fftw3_rust::Fftw;
+//! use num::complex::Cmplx;
+//! use sync::Arc;
+//!
+//! let zeros = std::vec::from_elem(64 * 64, Cmplx::new(0f64, 0f64));
+//! let fftw = Fftw::from_slice_nd([64, 64], zeros).unwrap();
+//! let plan = Arc::new(fftw.into_shared());
+//! for k in range(0, 4) {
+//!   let plan = plan.clone();
+//!   spawn(proc() {
+//!     let mut input = std::vec::from_elem(64 * 64, Cmplx::new(k as f64, 0f64));
+//!     let mut output = std::vec::from_elem(64 * 64, Cmplx::new(0f64, 0f64));
+//!     plan.get().execute(input.as_mut_slice(), output.as_mut_slice()).unwrap();
+//!   });
+//! }
+//! ```
+
+use super::{Fftw, FftBuf, FftwError, TransformData, Plan, PlannedArrays};
+
+/// The plan of a transform, detached from its buffers. It computes the transform on any
+/// arrays matching the planned ones : of the same length, and with the same alignment
+/// (arrays allocated by fftw3 are always properly aligned).
+pub struct SharedPlan<Tin, Tout> {
+  priv plan: Plan,
+  priv arrays: PlannedArrays,
+  priv shape: ~[uint],
+}
+
+impl<Tin: TransformData, Tout: TransformData> Fftw<FftBuf<Tin>, FftBuf<Tout>> {
+  /// Turns this transform into a plan shared by several tasks. The buffers of the
+  /// transform are freed.
+  pub fn into_shared(self) -> SharedPlan<Tin, Tout> {
+    let arrays = self.planned_arrays();
+    let Fftw { plan, shape, .. } = self;
+    SharedPlan {
+      plan: plan,
+      arrays: arrays,
+      shape: shape,
+    }
+  }
+}
+
+impl<Tin: TransformData, Tout: TransformData> SharedPlan<Tin, Tout> {
+  /// Computes the transform of 'input' into 'output'. Several tasks can call it at once
+  /// on distinct arrays. As for `Fftw`, a complex to real transform overwrites its input.
+  /// Returns an error if the arrays do not match the planned ones.
+  pub fn execute(&self, input: &mut [Tin], output: &mut [Tout]) -> Result<(), FftwError> {
+    if_ok!(self.arrays.check(input, output));
+    unsafe {
+      self.plan.execute_with(input, output, self.arrays.offsets);
+    }
+    Ok(())
+  }
+
+  /// Returns the dimensions of the transform, in row-major order.
+  pub fn shape<'a>(&'a self) -> &'a [uint] {
+    self.shape.as_slice()
+  }
+
+  /// Returns the number of values of the input arrays.
+  pub fn input_len(&self) -> uint {
+    let (in_len, _) = self.arrays.lens;
+    in_len
+  }
+
+  /// Returns the number of values of the output arrays.
+  pub fn output_len(&self) -> uint {
+    let (_, out_len) = self.arrays.lens;
+    out_len
+  }
+}
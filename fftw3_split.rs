@@ -0,0 +1,280 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Transforms over complex values stored in split format : the real parts and the
+//! imaginary parts in two separate arrays.
+//!
+//! The arrays of a `SplitFftw` are given each time the transform is computed. A plan of
+//! fftw3 only computes split arrays at the distances between the real and imaginary
+//! parts it was made for, so the transform is planned for the arrays of each call, with
+//! `Estimate` since the other rigors would overwrite them. The plan of the wisdom is
+//! used instead if it holds one of the rigor of the planner for the same distances, and
+//! the plans are reused while the plan memo is enabled, see `enable_memo`.
+//!
+//! The plans are made for unaligned arrays, so any slice can be used. The complex and
+//! real to complex transforms are planned to preserve their input, which is why they
+//! take it by shared reference.
+//!
+//! ```rust
+//! use fftw3_rust::SplitFftw;
+//!
+//! let (re, im) = ([1f64, 2f64, 0f64, 4f64], [0f64, -1f64, 3f64, 4f64]);
+//! let (mut ro, mut io) = ([0f64, ..4], [0f64, ..4]);
+//! let fftw = SplitFftw::new([4]).unwrap();
+//! fftw.execute(re, im, &mut ro, &mut io).unwrap();
+//! ```
+
+use fftw3_bindgen::{FFTW_WISDOM_ONLY, ptrdiff_t, fftw_iodim64, fftw_plan,
+                    Struct_fftw_iodim64_do_not_use_me,
+                    fftw_plan_guru64_split_dft, fftw_plan_guru64_split_dft_r2c,
+                    fftw_plan_guru64_split_dft_c2r, fftw_execute_split_dft,
+                    fftw_execute_split_dft_r2c, fftw_execute_split_dft_c2r};
+
+use std::libc::{c_int, c_uint};
+use std::mem::size_of;
+use std::ptr::null;
+
+use super::{FftwError, SizeMismatch, KindMismatch, check_shape, half_shape, Plan, Double,
+            Estimate, Planner, ToPlanner, TransformKind, Forward, Backward, RealToComplex,
+            ComplexToReal};
+use fftw3_cache::describe_layout;
+use fftw3_memo::plan_memoized;
+
+/// A transform over complex values in split format, planned for the arrays of each call.
+pub struct SplitFftw {
+  priv shape: ~[uint],
+  priv dims: ~[fftw_iodim64],
+  priv planner: Planner,
+  priv kind: TransformKind,
+  priv real_len: uint,
+  priv cmplx_len: uint,
+}
+
+impl SplitFftw {
+  /// Prepare a new transform over an array of complex values of arbitrary rank, stored
+  /// in row-major order.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  pub fn new(shape: &[uint]) -> Result<SplitFftw, FftwError> {
+    SplitFftw::new_with(shape, Estimate)
+  }
+
+  /// Same as `new`, planned with the options of 'planner'.
+  pub fn new_with<P: ToPlanner>(shape: &[uint], planner: P)
+                                -> Result<SplitFftw, FftwError> {
+    SplitFftw::prepare(shape, Forward, planner)
+  }
+
+  /// Prepare a new inverse transform over an array of complex values of arbitrary rank,
+  /// stored in row-major order.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  pub fn new_inv(shape: &[uint]) -> Result<SplitFftw, FftwError> {
+    SplitFftw::new_inv_with(shape, Estimate)
+  }
+
+  /// Same as `new_inv`, planned with the options of 'planner'.
+  pub fn new_inv_with<P: ToPlanner>(shape: &[uint], planner: P)
+                                    -> Result<SplitFftw, FftwError> {
+    SplitFftw::prepare(shape, Backward, planner)
+  }
+
+  /// Prepare a new transform over an array of real values of arbitrary rank, stored in
+  /// row-major order. Only the n/2+1 first values of the last dimension are computed.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  pub fn new_r2c(shape: &[uint]) -> Result<SplitFftw, FftwError> {
+    SplitFftw::new_r2c_with(shape, Estimate)
+  }
+
+  /// Same as `new_r2c`, planned with the options of 'planner'.
+  pub fn new_r2c_with<P: ToPlanner>(shape: &[uint], planner: P)
+                                    -> Result<SplitFftw, FftwError> {
+    SplitFftw::prepare(shape, RealToComplex, planner)
+  }
+
+  /// Prepare a new inverse transform producing an array of real values of arbitrary
+  /// rank, stored in row-major order. The input only holds the n/2+1 first values of
+  /// the last dimension.
+  /// Returns an error if 'shape' is empty or if one of its dimensions is zero.
+  pub fn new_c2r(shape: &[uint]) -> Result<SplitFftw, FftwError> {
+    SplitFftw::new_c2r_with(shape, Estimate)
+  }
+
+  /// Same as `new_c2r`, planned with the options of 'planner'.
+  pub fn new_c2r_with<P: ToPlanner>(shape: &[uint], planner: P)
+                                    -> Result<SplitFftw, FftwError> {
+    SplitFftw::prepare(shape, ComplexToReal, planner)
+  }
+
+  fn prepare<P: ToPlanner>(shape: &[uint], kind: TransformKind,
+                           planner: P) -> Result<SplitFftw, FftwError> {
+    let real_len = if_ok!(check_shape(shape));
+    let half = half_shape(shape);
+    let (in_shape, out_shape) = match kind {
+      RealToComplex => (shape, half.as_slice()),
+      ComplexToReal => (half.as_slice(), shape),
+      Forward | Backward => (shape, shape),
+    };
+    let cmplx_len = match kind {
+      Forward | Backward => real_len,
+      RealToComplex | ComplexToReal => half.iter().fold(1u, |a, &b| a * b),
+    };
+    // Only the complex to real transform takes its input mutably, the other ones must
+    // not write it whatever the planner asks for.
+    let planner = match kind {
+      ComplexToReal => planner.to_planner().with_unaligned(),
+      Forward | Backward | RealToComplex =>
+        planner.to_planner().with_unaligned().with_preserved_input(),
+    };
+
+    Ok(SplitFftw {
+      shape: shape.to_owned(),
+      dims: split_dims(in_shape, out_shape),
+      planner: planner,
+      kind: kind,
+      real_len: real_len,
+      cmplx_len: cmplx_len,
+    })
+  }
+
+  /// Returns the plan of the transform from the real and imaginary parts 'ri' and 'ii'
+  /// to 'ro' and 'io'. The real transforms only use 'ri', or 'ro'. The plan is made
+  /// for these arrays, see the module documentation.
+  unsafe fn plan(&self, ri: *mut f64, ii: *mut f64, ro: *mut f64,
+                 io: *mut f64) -> Result<Plan, FftwError> {
+    let (name, layout) = match self.kind {
+      // The inverse transform swaps the real and imaginary parts of the same plan.
+      Forward | Backward =>
+        ("split-dft", format!("distances {}/{}", distance(ri, ii), distance(ro, io))),
+      RealToComplex => ("split-r2c", format!("distance {}", distance(ro, io))),
+      ComplexToReal => ("split-c2r", format!("distance {}", distance(ri, ii))),
+    };
+    let transform = describe_layout(name, self.shape.as_slice(), layout, false);
+    let wisdom_flags = self.planner.fftw_flags() | FFTW_WISDOM_ONLY;
+    let estimate = self.planner.clone().with_rigor(Estimate);
+    plan_memoized(&estimate, Double, transform, ri, ro, |flags| {
+      let raw = self.make(wisdom_flags, ri, ii, ro, io);
+      let raw = if raw.is_null() { self.make(flags, ri, ii, ro, io) } else { raw };
+      Plan::new(raw, Double)
+    })
+  }
+
+  /// Calls the split planner of the transform with 'flags'.
+  unsafe fn make(&self, flags: c_uint, ri: *mut f64, ii: *mut f64, ro: *mut f64,
+                 io: *mut f64) -> fftw_plan {
+    let (rank, dims) = (self.dims.len() as c_int, self.dims.as_ptr());
+    match self.kind {
+      Forward | Backward =>
+        fftw_plan_guru64_split_dft(rank, dims, 0, null(), ri, ii, ro, io, flags),
+      RealToComplex =>
+        fftw_plan_guru64_split_dft_r2c(rank, dims, 0, null(), ri, ro, io, flags),
+      ComplexToReal =>
+        fftw_plan_guru64_split_dft_c2r(rank, dims, 0, null(), ri, ii, ro, flags),
+    }
+  }
+
+  /// Compute the complex transform of the values whose real parts are in 're' and
+  /// imaginary parts are in 'im', into 'out_re' and 'out_im'. The input values are left
+  /// untouched.
+  /// Returns an error if this is a real transform, if the length of one of the arrays is
+  /// not the size of the transform, or if fftw3 could not plan it for these arrays.
+  pub fn execute(&self, re: &[f64], im: &[f64], out_re: &mut [f64], out_im: &mut [f64])
+                 -> Result<(), FftwError> {
+    if self.kind != Forward && self.kind != Backward {
+      return Err(KindMismatch);
+    }
+    if_ok!(check_len(self.real_len, re.len()));
+    if_ok!(check_len(self.real_len, im.len()));
+    if_ok!(check_len(self.real_len, out_re.len()));
+    if_ok!(check_len(self.real_len, out_im.len()));
+    let (ri, ii) = (re.as_ptr() as *mut f64, im.as_ptr() as *mut f64);
+    let (ro, io) = (out_re.as_mut_ptr(), out_im.as_mut_ptr());
+    unsafe {
+      // The inverse transform is the forward one with the real and imaginary parts
+      // swapped, in the input and in the output.
+      if self.kind == Forward {
+        let plan = if_ok!(self.plan(ri, ii, ro, io));
+        fftw_execute_split_dft(plan.raw, ri, ii, ro, io);
+      } else {
+        let plan = if_ok!(self.plan(ii, ri, io, ro));
+        fftw_execute_split_dft(plan.raw, ii, ri, io, ro);
+      }
+    }
+    Ok(())
+  }
+
+  /// Compute the transform of the real values of 'input', into the n/2+1 first
+  /// complex values of the last dimension, split in 'out_re' and 'out_im'. The input
+  /// values are left untouched.
+  /// Returns an error if this is not a real to complex transform, if the length of one
+  /// of the arrays does not match the size of the transform, or if fftw3 could not plan
+  /// it for these arrays.
+  pub fn execute_r2c(&self, input: &[f64], out_re: &mut [f64], out_im: &mut [f64])
+                     -> Result<(), FftwError> {
+    if self.kind != RealToComplex {
+      return Err(KindMismatch);
+    }
+    if_ok!(check_len(self.real_len, input.len()));
+    if_ok!(check_len(self.cmplx_len, out_re.len()));
+    if_ok!(check_len(self.cmplx_len, out_im.len()));
+    let ri = input.as_ptr() as *mut f64;
+    let (ro, io) = (out_re.as_mut_ptr(), out_im.as_mut_ptr());
+    unsafe {
+      let plan = if_ok!(self.plan(ri, ri, ro, io));
+      fftw_execute_split_dft_r2c(plan.raw, ri, ro, io);
+    }
+    Ok(())
+  }
+
+  /// Compute the real values of the inverse transform of the n/2+1 first complex values
+  /// of the last dimension, split in 're' and 'im', into 'output'. As with every complex
+  /// to real transform, the input values are overwritten.
+  /// Returns an error if this is not a complex to real transform, if the length of one
+  /// of the arrays does not match the size of the transform, or if fftw3 could not plan
+  /// it for these arrays.
+  pub fn execute_c2r(&self, re: &mut [f64], im: &mut [f64], output: &mut [f64])
+                     -> Result<(), FftwError> {
+    if self.kind != ComplexToReal {
+      return Err(KindMismatch);
+    }
+    if_ok!(check_len(self.cmplx_len, re.len()));
+    if_ok!(check_len(self.cmplx_len, im.len()));
+    if_ok!(check_len(self.real_len, output.len()));
+    let (ri, ii, ro) = (re.as_mut_ptr(), im.as_mut_ptr(), output.as_mut_ptr());
+    unsafe {
+      let plan = if_ok!(self.plan(ri, ii, ro, ro));
+      fftw_execute_split_dft_c2r(plan.raw, ri, ii, ro);
+    }
+    Ok(())
+  }
+}
+
+/// Returns the distance in values from the array 'a' to the array 'b', which fftw3
+/// expects to be the planned one when computing the transform on other arrays.
+fn distance(a: *mut f64, b: *mut f64) -> int {
+  (b as int - a as int) / size_of::<f64>() as int
+}
+
+fn check_len(expected: uint, actual: uint) -> Result<(), FftwError> {
+  if expected == actual {
+    Ok(())
+  } else {
+    Err(SizeMismatch(expected, actual))
+  }
+}
+
+/// Returns the guru dimensions of a transform between two contiguous arrays, stored in
+/// row-major order.
+fn split_dims(in_shape: &[uint], out_shape: &[uint]) -> ~[fftw_iodim64] {
+  let rank = in_shape.len();
+  let mut dims = ::std::vec::with_capacity(rank);
+  let (mut is, mut os) = (1u, 1u);
+  for i in range(0, rank).rev() {
+    // The size of the transform along the last dimension is the one of the real array.
+    let n = if in_shape[i] > out_shape[i] { in_shape[i] } else { out_shape[i] };
+    dims.unshift(Struct_fftw_iodim64_do_not_use_me {
+      n: n as ptrdiff_t,
+      is: is as ptrdiff_t,
+      os: os as ptrdiff_t,
+    });
+    is *= in_shape[i];
+    os *= out_shape[i];
+  }
+  dims
+}
@@ -3,11 +3,25 @@
 
 use extra::time::precise_time_ns;
 use num::complex::Cmplx;
+use sync::mutex::{StaticMutex, MUTEX_INIT};
 
-use super::{Fftw, TransformData};
+use super::{Fftw, Transform, ZeroRank, ZeroDimension, SizeMismatch, InvalidLayout,
+            EmptyBatch, NoPlan, FftwError, UnsupportedSize, R2rKind, Dct1, Dct2, Dct3,
+            Dct4, Dst1, Dst2, Dst3, Dst4, Dht, halfcomplex_to_cmplx, cmplx_to_halfcomplex,
+            GuruLayout, IoDim, OutOfBounds, SplitFftw, KindMismatch, Misaligned,
+            InPlaceFftw, FftBuf, TransformBuf, FftwFloat, Line, Estimate, Measure,
+            Patient, Planner, ToPlanner, Wisdom, InvalidWisdom, WisdomFile, init_wisdom,
+            PlanCache, FftwPool, enable_memo, memo_capacity, memo_stats, clear_memo};
+#[cfg(fftw3_threads)]
+use super::init_threads;
 
 mod fftw3_macros;
 
+/// Taken by the tests changing the global state of the planner : the wisdom, which
+/// planning with more rigor than `Estimate` adds to, the threads and the plan memo. They
+/// do not change it under each other while the tests run in parallel.
+static mut PLANNER_STATE_LOCK: StaticMutex = MUTEX_INIT;
+
 #[test]
 fn test_1d_cmplx() {
   let inp = ca!{48 -2, 39 +5, 37 +3, 55 +0, 22 +210};
@@ -135,7 +149,789 @@ fn test_index() {
   fftw.input().get(8).is_none() || fail!();
 }
 
-fn bench<T: TransformData>(slice: &[T], domain: &str) {
+fn approx_eq(a: Cmplx<f64>, b: Cmplx<f64>) -> bool {
+  (a - b).norm() < 1e-9
+}
+
+#[test]
+fn test_2d_cmplx() {
+  let inp = ca!{1 +0, 2 -1, 0 +3, 4 +4, 5 -2, 1 +1};
+  let mut fftw = Fftw::new_2d(2, 3).unwrap();
+  fftw.ref_input().push_slice(inp) || fail!();
+  fftw.compute().unwrap();
+  assert!(approx_eq(fftw.output()[0], c!(13, 5)));
+  assert!(fftw.output_rows().count() == 2);
+  for row in fftw.output_rows() {
+    assert!(row.len() == 3);
+  }
+  assert!(Fftw::new_2d(2, 0).err() == Some(ZeroDimension(1)));
+  assert!(Fftw::new_3d_r2c(0, 2, 2).err() == Some(ZeroDimension(0)));
+}
+
+#[test]
+fn test_3d_cmplx_roundtrip() {
+  let inp = ca!{1 +0, 2 -1, 0 +3, 4 +4, 5 -2, 1 +1, (-3) +2, 0 -7};
+  let mut fwd = Fftw::new_3d(2, 2, 2).unwrap();
+  fwd.ref_input().push_slice(inp);
+  let mut inv = Fftw::new_3d_inv(2, 2, 2).unwrap();
+  inv.ref_input().push_slice(fwd.compute().unwrap());
+  for (i, o) in inp.iter().zip(inv.compute().unwrap().iter()) {
+    assert!(approx_eq(*i, o.unscale(8f64)));
+  }
+  assert!(inv.shape() == [2, 2, 2]);
+}
+
+#[test]
+fn test_nd_cmplx() {
+  let inp = ca!{1 +0, 2 -1, 0 +3, 4 +4, 5 -2, 1 +1, (-3) +2, 0 -7,
+                2 +2, 1 -1, 3 +0, 0 +1, (-1) -1, 6 +2, 0 +0, 1 +1};
+  let mut fftw = Fftw::from_slice_nd([2, 2, 2, 2], inp).unwrap();
+  let mut sum = c!(0);
+  for i in inp.iter() {
+    sum = sum + *i;
+  }
+  assert!(approx_eq(fftw.compute().unwrap()[0], sum));
+}
+
+#[test]
+fn test_nd_invalid_shape() {
+  assert!(Fftw::new_nd([]).err() == Some(ZeroRank));
+  assert!(Fftw::new_nd_inv([4, 0, 2]).err() == Some(ZeroDimension(1)));
+  assert!(Fftw::from_slice_nd([2, 3], ca!{1 +0, 2 +0}).err() == Some(SizeMismatch(6, 2)));
+}
+
+#[test]
+fn test_2d_real_roundtrip() {
+  let inp = ra!{1, 0, 2, 4, 5, 2, 0, -1, -3, 7, 1, 1};
+  let mut fwd = Fftw::new_2d_r2c(3, 4).unwrap();
+  fwd.ref_input().push_slice(inp);
+  fwd.compute().unwrap();
+  assert!(fwd.output_shape() == ~[3, 3]);
+  assert!(fwd.input_shape() == ~[3, 4]);
+  let mut inv = Fftw::new_2d_c2r(3, 4).unwrap();
+  inv.ref_input().push_slice(fwd.output());
+  assert!(inv.input_shape() == ~[3, 3]);
+  for (i, o) in inp.iter().zip(inv.compute().unwrap().iter()) {
+    assert!((*i - *o/12f64).abs() < 1e-9);
+  }
+}
+
+#[test]
+fn test_nd_real() {
+  let mut fftw = Fftw::new_nd_r2c([2, 2, 2, 5]).unwrap();
+  for i in range(0, 40) {
+    fftw.ref_input().push(i as f64);
+  }
+  fftw.compute().unwrap();
+  assert!(fftw.output().len() == 24);
+  assert!(approx_eq(fftw.output()[0], c!(780)));
+  assert!(Fftw::new_nd_c2r([3, 0]).err() == Some(ZeroDimension(1)));
+}
+
+#[test]
+fn test_many_cmplx() {
+  let signals = [ca!{1 +0, 2 -1, 0 +3, 4 +4}, ca!{5 -2, 1 +1, (-3) +2, 0 -7}];
+  // Interleaved signals
+  let mut fftw = Fftw::new_many(4, 2, 2, 1).unwrap();
+  for i in range(0u, 4) {
+    fftw.ref_input().push(signals[0][i]);
+    fftw.ref_input().push(signals[1][i]);
+  }
+  fftw.compute().unwrap();
+  assert!(fftw.shape() == [4]);
+  assert!(fftw.output_shape() == ~[4, 2]);
+  for (i, row) in fftw.output_rows().enumerate() {
+    assert!(row.len() == 2);
+    assert!(approx_eq(row[1], fftw.output()[2*i + 1]));
+  }
+  for (k, signal) in signals.iter().enumerate() {
+    let mut single = Fftw::from_slice(signal.as_slice());
+    for (i, cx) in single.compute().unwrap().iter().enumerate() {
+      assert!(approx_eq(fftw.output()[2*i + k], *cx));
+    }
+  }
+}
+
+#[test]
+fn test_many_real() {
+  let inp = ra!{1, 0, 2, 4, 5, 2, 0, -1, -3, 7, 1, 1};
+  let mut fwd = Fftw::new_many_r2c(4, 3, 1, 4).unwrap();
+  fwd.ref_input().push_slice(inp);
+  fwd.compute().unwrap();
+  assert!(fwd.shape() == [4]);
+  assert!(fwd.input_shape() == ~[3, 4]);
+  assert!(fwd.output_shape() == ~[3, 3]);
+  assert!(fwd.output_rows().count() == 3);
+  let mut inv = Fftw::new_many_c2r(4, 3, 1, 4).unwrap();
+  inv.ref_input().push_slice(fwd.output());
+  for (i, o) in inp.iter().zip(inv.compute().unwrap().iter()) {
+    assert!((*i - *o/4f64).abs() < 1e-9);
+  }
+  assert!(Fftw::new_many(4, 3, 1, 2).err() == Some(InvalidLayout));
+  assert!(Fftw::new_many(4, 0, 1, 4).err() == Some(EmptyBatch));
+  assert!(Fftw::new_many(0, 3, 1, 4).err() == Some(ZeroDimension(0)));
+}
+
+fn check_r2r_roundtrip(shape: &[uint], kind: R2rKind, inp: &[f64]) {
+  let mut fwd = Fftw::new_r2r_nd(shape, kind).unwrap();
+  fwd.ref_input().push_slice(inp);
+  let mut inv = Fftw::new_r2r_nd(shape, kind.inverse()).unwrap();
+  inv.ref_input().push_slice(fwd.compute().unwrap());
+  let scale = shape.iter().fold(1f64, |s, &n| s * kind.scale(n));
+  for (i, o) in inp.iter().zip(inv.compute().unwrap().iter()) {
+    assert!((*i - *o/scale).abs() < 1e-9);
+  }
+}
+
+#[test]
+fn test_dct() {
+  let inp = ra!{1, 2, 3, 4};
+  let mut fftw = Fftw::from_slice_r2r(inp, Dct2).unwrap();
+  assert!((fftw.compute().unwrap()[0] - 20f64).abs() < 1e-9);
+
+  let inp = ra!{1, 0, 2, 4, 5, 2, 0, -1, -3, 7, 1, 1};
+  for kind in [Dct1, Dct2, Dct3, Dct4].iter() {
+    check_r2r_roundtrip([12], *kind, inp);
+    check_r2r_roundtrip([3, 4], *kind, inp);
+  }
+  assert!(Fftw::new_r2r(1, Dct1).err() == Some(UnsupportedSize(0)));
+}
+
+#[test]
+fn test_dst() {
+  let inp = ra!{1, 0, 2, 4, 5, 2, 0, -1, -3, 7, 1, 1};
+  for kind in [Dst1, Dst2, Dst3, Dst4].iter() {
+    check_r2r_roundtrip([12], *kind, inp);
+    check_r2r_roundtrip([2, 3, 2], *kind, inp);
+  }
+}
+
+#[test]
+fn test_r2r_mixed() {
+  let inp = ra!{1, 0, 2, 4, 5, 2, 0, -1, -3, 7, 1, 1};
+  let kinds = [Dct2, Dst1];
+  let inv_kinds: ~[R2rKind] = kinds.iter().map(|k| k.inverse()).collect();
+  let mut fwd = Fftw::new_r2r_mixed([3, 4], kinds).unwrap();
+  fwd.ref_input().push_slice(inp);
+  let mut inv = Fftw::new_r2r_mixed([3, 4], inv_kinds).unwrap();
+  inv.ref_input().push_slice(fwd.compute().unwrap());
+  let scale = Dct2.scale(3) * Dst1.scale(4);
+  for (i, o) in inp.iter().zip(inv.compute().unwrap().iter()) {
+    assert!((*i - *o/scale).abs() < 1e-9);
+  }
+  assert!(Fftw::new_r2r_mixed([3, 4], [Dct2]).err() == Some(SizeMismatch(2, 1)));
+}
+
+#[test]
+fn test_halfcomplex() {
+  for inp in [hra!{1, 0, 2, 4, 5, 2, 0, -1, -3}, hra!{1, 0, 2, 4, 5, 2, 0, -1}].iter() {
+    let mut r2c = Fftw::from_slice(inp.as_slice());
+    let mut r2hc = Fftw::new_r2hc(inp.len()).unwrap();
+    r2hc.ref_input().push_slice(inp.as_slice());
+    let hc = r2hc.compute().unwrap();
+    let half = halfcomplex_to_cmplx(hc);
+    for (i, j) in half.iter().zip(r2c.compute().unwrap().iter()) {
+      assert!(approx_eq(*i, *j));
+    }
+    assert!(cmplx_to_halfcomplex(half, inp.len()).unwrap().as_slice() == hc);
+
+    let mut hc2r = Fftw::new_hc2r(inp.len()).unwrap();
+    hc2r.ref_input().push_slice(hc);
+    for (i, o) in inp.iter().zip(hc2r.compute().unwrap().iter()) {
+      assert!((*i - *o/inp.len() as f64).abs() < 1e-9);
+    }
+  }
+
+  // An empty array has no values, the middle value of an odd length has a pair.
+  assert!(halfcomplex_to_cmplx([]).is_empty());
+  assert!(cmplx_to_halfcomplex([], 0).unwrap().is_empty());
+  assert!(cmplx_to_halfcomplex(ca!{1 +0}, 0).err() == Some(SizeMismatch(0, 1)));
+  assert!(halfcomplex_to_cmplx([5f64]) == ~[c!(5)]);
+  let half = halfcomplex_to_cmplx([1f64, 2f64, 3f64]);
+  assert!(half == ~[c!(1), c!(2, 3)]);
+  assert!(cmplx_to_halfcomplex(half, 3).unwrap() == ~[1f64, 2f64, 3f64]);
+}
+
+#[test]
+fn test_dht() {
+  let inp = ra!{1, 0, 2, 4, 5, 2, 0, -1, -3};
+  check_r2r_roundtrip([9], Dht, inp);
+  let mut fftw = Fftw::new_dht(9).unwrap();
+  fftw.ref_input().push_slice(inp);
+  assert!((fftw.compute().unwrap()[0] - 10f64).abs() < 1e-9);
+}
+
+#[test]
+fn test_guru_sub_block() {
+  // Transform the 2x2 block starting at (1, 1) in a 3x4 array, into a packed output.
+  let inp = ca!{0 +0, 0 +0, 0 +0, 0 +0,
+                0 +0, 1 +2, 3 -1, 0 +0,
+                0 +0, 4 +0, (-2) +5, 0 +0};
+  let layout = GuruLayout::new(~[IoDim { n: 2, is: 4, os: 2 },
+                                 IoDim { n: 2, is: 1, os: 1 }], ~[]).with_offsets(5, 0);
+  let mut guru = Fftw::new_guru(&layout, 12, 4).unwrap();
+  guru.ref_input().push_slice(inp);
+  let mut packed = Fftw::from_slice_nd([2, 2], ca!{1 +2, 3 -1, 4 +0, (-2) +5}).unwrap();
+  for (i, j) in guru.compute().unwrap().iter().zip(packed.compute().unwrap().iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+
+  assert!(Fftw::new_guru(&layout, 11, 4).err() == Some(OutOfBounds));
+  let reversed = GuruLayout::new(~[IoDim { n: 4, is: -1, os: 1 }], ~[]);
+  assert!(Fftw::new_guru(&reversed.clone().with_offsets(3, 0), 4, 4).is_ok());
+  assert!(Fftw::new_guru(&reversed, 4, 4).err() == Some(OutOfBounds));
+}
+
+#[test]
+fn test_split_cmplx() {
+  use std::vec::from_elem;
+
+  let inp = ca!{1 +0, 2 -1, 0 +3, 4 +4, 5 -2, 1 +1};
+  let re: ~[f64] = inp.iter().map(|c| c.re).collect();
+  let im: ~[f64] = inp.iter().map(|c| c.im).collect();
+  let (mut ro, mut io) = ([0f64, ..6], [0f64, ..6]);
+  let split = SplitFftw::new([2, 3]).unwrap();
+  split.execute(re, im, &mut ro, &mut io).unwrap();
+  let mut fftw = Fftw::new_2d(2, 3).unwrap();
+  fftw.ref_input().push_slice(inp);
+  for (i, cx) in fftw.compute().unwrap().iter().enumerate() {
+    assert!(approx_eq(*cx, c!(ro[i], io[i])));
+  }
+
+  // The arrays of each call are unrelated allocations, at other distances from each
+  // other than the ones of the previous calls.
+  let inv = SplitFftw::new_inv([2, 3]).unwrap();
+  for n in range(1u, 4u) {
+    let re: ~[f64] = ro.to_owned();
+    let _spacer = from_elem(n * 37, 0f64);
+    let im: ~[f64] = io.to_owned();
+    let mut rb = from_elem(6, 0f64);
+    let _spacer = from_elem(n * 11, 0f64);
+    let mut ib = from_elem(6, 0f64);
+    inv.execute(re.as_slice(), im.as_slice(), rb.as_mut_slice(),
+                ib.as_mut_slice()).unwrap();
+    for i in range(0u, 6) {
+      assert!(approx_eq(inp[i], c!(rb[i]/6f64, ib[i]/6f64)));
+    }
+  }
+  assert!(split.execute_r2c(re, &mut ro, &mut io).err() == Some(KindMismatch));
+}
+
+#[test]
+fn test_split_real() {
+  let inp = ra!{1, 0, 2, 4, 5, 2, 0, -1, -3};
+  let (mut ro, mut io) = ([0f64, ..5], [0f64, ..5]);
+  SplitFftw::new_r2c([9]).unwrap().execute_r2c(inp, &mut ro, &mut io).unwrap();
+  let mut fftw = Fftw::from_slice(inp);
+  for (i, cx) in fftw.compute().unwrap().iter().enumerate() {
+    assert!(approx_eq(*cx, c!(ro[i], io[i])));
+  }
+
+  let mut out = [0f64, ..9];
+  let c2r = SplitFftw::new_c2r([9]).unwrap();
+  assert!(c2r.execute_c2r(&mut ro, &mut io, out.mut_slice_to(8)).err() == Some(SizeMismatch(9, 8)));
+  c2r.execute_c2r(&mut ro, &mut io, &mut out).unwrap();
+  for (i, o) in inp.iter().zip(out.iter()) {
+    assert!((*i - *o/9f64).abs() < 1e-9);
+  }
+}
+
+#[test]
+fn test_compute_with() {
+  let mut fftw = Fftw::new(9);
+  let mut inp: FftBuf<f64> = TransformBuf::new(9);
+  inp.push_slice(ra!{1, 0, 2, 4, 5, 2, 0, -1, -3});
+  let mut out: FftBuf<Cmplx<f64>> = TransformBuf::new(5);
+  out.mark_filled();
+  fftw.compute_with(inp.as_mut_slice(), out.as_mut_slice()).unwrap();
+
+  fftw.ref_input().push_slice(inp.as_slice());
+  for (i, j) in fftw.compute().unwrap().iter().zip(out.iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+
+  let mut short = [c!(0), ..4];
+  assert!(fftw.compute_with(inp.as_mut_slice(), &mut short).err() == Some(SizeMismatch(5, 4)));
+  // fftw3 buffers are aligned for simd, so skipping one value breaks the alignment.
+  let mut larger: FftBuf<f64> = TransformBuf::new(10);
+  larger.mark_filled();
+  assert!(fftw.compute_with(larger.as_mut_slice().mut_slice_from(1), out.as_mut_slice()).err() ==
+          Some(Misaligned));
+}
+
+fn assert_send_share<T: Send + Share>(_: &T) {
+}
+
+#[test]
+fn test_shared_plan() {
+  use sync::Arc;
+
+  let inp = ca!{1 +0, 2 -1, 0 +3, 4 +4, 5 -2, 1 +1, 0 +0, 3 -3};
+  let mut fftw = Fftw::from_slice_nd([2, 4], inp).unwrap();
+  let expected = fftw.compute().unwrap().to_owned();
+  let plan = fftw.into_shared();
+  assert_send_share(&plan);
+  assert!(plan.shape() == [2, 4] && plan.input_len() == 8 && plan.output_len() == 8);
+
+  let plan = Arc::new(plan);
+  let (tx, rx) = channel();
+  for k in range(1, 5) {
+    let (plan, tx) = (plan.clone(), tx.clone());
+    spawn(proc() {
+      let mut input: FftBuf<Cmplx<f64>> = TransformBuf::new(8);
+      for &cx in inp.iter() {
+        input.push(cx.scale(k as f64));
+      }
+      let mut output: FftBuf<Cmplx<f64>> = TransformBuf::new(8);
+      output.mark_filled();
+      plan.get().execute(input.as_mut_slice(), output.as_mut_slice()).unwrap();
+      tx.send((k, output.as_slice().to_owned()));
+    });
+  }
+  for _ in range(0, 4) {
+    let (k, out) = rx.recv();
+    for (i, j) in out.iter().zip(expected.iter()) {
+      assert!(approx_eq(*i, j.scale(k as f64)));
+    }
+  }
+
+  let mut short = [c!(0), ..4];
+  let mut input: FftBuf<Cmplx<f64>> = TransformBuf::new(8);
+  input.mark_filled();
+  let res = plan.get().execute(input.as_mut_slice(), &mut short);
+  assert!(res.err() == Some(SizeMismatch(8, 4)));
+}
+
+#[test]
+fn test_pool() {
+  let _g = unsafe { PLANNER_STATE_LOCK.lock() };
+  let mut pool: FftwPool<f64> = FftwPool::new(3);
+  let signals: ~[~[Cmplx<f64>]] = range(0u, 20u).map(|k| {
+    range(0u, k % 7).map(|i| c!(i * k, k)).collect()
+  }).collect();
+  for signal in signals.iter() {
+    pool.submit(signal.clone());
+  }
+  assert!(pool.pending() == 20);
+  for (spectrum, signal) in pool.by_ref().zip(signals.iter()) {
+    if signal.is_empty() {
+      assert!(spectrum.err() == Some(ZeroDimension(0)));
+      continue;
+    }
+    let mut fftw = Fftw::from_slice_nd([signal.len()], signal.as_slice()).unwrap();
+    for (i, j) in spectrum.unwrap().iter().zip(fftw.compute().unwrap().iter()) {
+      assert!(approx_eq(*i, *j));
+    }
+  }
+  assert!(pool.pending() == 0 && pool.next().is_none());
+
+  let mut inv: FftwPool<f64> = FftwPool::new_inv_with(2, Measure);
+  let signals = ~[hca!{8 +0, 0 +0, 0 +0, 0 +0}, hca!{2 +2}];
+  let spectra: ~[~[Cmplx<f64>]] = inv.compute_all(signals).move_iter().map(|spectrum| {
+    spectrum.unwrap()
+  }).collect();
+  assert!(approx_eq(spectra[0][3], c!(8)) && approx_eq(spectra[1][0], c!(2, 2)));
+
+  // The signals submitted before are left to the iterator.
+  inv.submit(hca!{4 +0, 0 +0});
+  let spectrum = inv.compute_all(~[hca!{1 +1}]).move_iter().next().unwrap().unwrap();
+  assert!(approx_eq(spectrum[0], c!(1, 1)) && inv.pending() == 1);
+  assert!(approx_eq(inv.next().unwrap().unwrap()[1], c!(4)));
+  assert!(inv.pending() == 0 && inv.next().is_none());
+}
+
+#[test]
+fn test_memo() {
+  use super::{Plan, Double};
+  use super::fftw3_memo::{Memo, MemoStats};
+
+  // A memo of its own does not change the one of the process.
+  let mut memo = Memo::new(2);
+  assert!(memo.find(&~"a").is_none());
+  memo.insert(~"a", Plan::empty(Double));
+  // A plan made meanwhile for the same key leaves the held one in place.
+  memo.insert(~"a", Plan::empty(Double));
+  assert!(memo.stats() == MemoStats { hits: 0, misses: 1, evictions: 0, plans: 1 });
+  assert!(memo.find(&~"a").is_some());
+  memo.insert(~"b", Plan::empty(Double));
+  memo.insert(~"c", Plan::empty(Double));
+  // The least recently used plan is evicted.
+  assert!(memo.find(&~"a").is_none() && memo.find(&~"b").is_some());
+  assert!(memo.stats() == MemoStats { hits: 2, misses: 2, evictions: 1, plans: 2 });
+  memo.set_capacity(0);
+  assert!(memo.stats().plans == 0 && memo.capacity() == 0);
+}
+
+#[test]
+fn test_plan_memo() {
+  use std::vec::from_elem;
+
+  // The other tests taking the lock expect the memo to be disabled.
+  let _g = unsafe { PLANNER_STATE_LOCK.lock() };
+  assert!(memo_capacity() == 0);
+  // No other test plans transforms of these lengths.
+  let signal: ~[Cmplx<f64>] = range(0u, 1031u).map(|k| c!(k % 7, k % 3)).collect();
+  let expected = {
+    let mut fftw = Fftw::from_slice(signal.as_slice());
+    let spectrum = fftw.compute().unwrap().to_owned();
+    spectrum
+  };
+
+  let memo = enable_memo(64);
+  let misses = memo_stats().misses;
+  {
+    let mut first = Fftw::from_slice(signal.as_slice());
+    assert!(memo_stats().misses > misses);
+    for (i, j) in first.compute().unwrap().iter().zip(expected.iter()) {
+      assert!(approx_eq(*i, *j));
+    }
+  }
+  // The plan made for the freed buffers of the first transform computes the second one.
+  let hits = memo_stats().hits;
+  let mut second = Fftw::from_slice(signal.as_slice());
+  assert!(memo_stats().hits > hits);
+  for (i, j) in second.compute().unwrap().iter().zip(expected.iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+
+  {
+    // A second guard sets the capacity, and keeps the memo enabled until dropped.
+    let _small = enable_memo(1);
+    for n in range(1033u, 1036u) {
+      let mut fftw: Fftw<FftBuf<f64>, FftBuf<Cmplx<f64>>> = Fftw::new(n);
+      fftw.ref_input().push_slice(from_elem(n, 1f64).as_slice());
+      assert!(approx_eq(fftw.compute().unwrap()[0], c!(n)));
+      assert!(memo_stats().plans <= 1);
+    }
+  }
+  assert!(memo_capacity() == 1 && memo_stats().evictions > 0);
+
+  // The plans cleared from the memo, or held by the destroyed memo, stay valid as long
+  // as a transform uses them.
+  clear_memo();
+  assert!(memo_stats().plans == 0);
+  let mut third = Fftw::from_slice(signal.as_slice());
+  drop(memo);
+  assert!(memo_capacity() == 0 && memo_stats().hits == 0);
+  for (i, j) in third.compute().unwrap().iter().zip(expected.iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+  second.ref_input().clear();
+  second.ref_input().push_slice(signal.as_slice());
+  for (i, j) in second.compute().unwrap().iter().zip(expected.iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+}
+
+#[test]
+fn test_inplace_real() {
+  let inp = ra!{1, 0, 2, 4, 5, 2, 0, -1, -3, 7, 1, 1};
+  let mut fwd: InPlaceFftw<f64> = InPlaceFftw::new_r2c([3, 4]).unwrap();
+  assert!(fwd.real().len() == 18);
+  for (row, src) in fwd.mut_real_rows().zip(inp.chunks(4)) {
+    row.mut_slice_to(4).copy_from(src);
+  }
+  fwd.compute();
+  let mut oop = Fftw::new_2d_r2c(3, 4).unwrap();
+  oop.ref_input().push_slice(inp);
+  for (i, j) in fwd.cmplx().iter().zip(oop.compute().unwrap().iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+
+  let mut inv: InPlaceFftw<f64> = InPlaceFftw::new_c2r([3, 4]).unwrap();
+  inv.mut_cmplx().copy_from(fwd.cmplx());
+  inv.compute();
+  for (row, src) in inv.real_rows().zip(inp.chunks(4)) {
+    for (o, i) in row.iter().zip(src.iter()) {
+      assert!((*i - *o/12f64).abs() < 1e-9);
+    }
+  }
+}
+
+#[test]
+fn test_inplace_cmplx() {
+  let inp = ca!{1+0, 2-1, 0+3, 4+4, 5-2, 1+1};
+  let mut fftw: InPlaceFftw<f64> = InPlaceFftw::new([2, 3]).unwrap();
+  fftw.mut_cmplx().copy_from(inp);
+  fftw.compute();
+  let mut oop = Fftw::new_2d(2, 3).unwrap();
+  oop.ref_input().push_slice(inp);
+  for (i, j) in fftw.cmplx().iter().zip(oop.compute().unwrap().iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+  let mut short = [c!(0), ..5];
+  assert!(fftw.compute_with(&mut short).err() == Some(SizeMismatch(6, 5)));
+
+  let single: ~[Cmplx<f32>] = inp.iter().map(|cx| Cmplx::new(cx.re as f32, cx.im as f32))
+                                 .collect();
+  let mut fftw32: InPlaceFftw<f32> = InPlaceFftw::new([2, 3]).unwrap();
+  fftw32.mut_cmplx().copy_from(single);
+  fftw32.compute();
+  for (i, j) in fftw32.cmplx().iter().zip(fftw.cmplx().iter()) {
+    assert!((i.re as f64 - j.re).abs() < 1e-4 && (i.im as f64 - j.im).abs() < 1e-4);
+  }
+}
+
+#[test]
+fn test_1d_f32() {
+  let inp = ra!{1, 0, 2, 4, 5, 2, 0, -1, -3};
+  let single: ~[f32] = inp.iter().map(|&x| x as f32).collect();
+  let mut fftw = Fftw::from_slice(inp);
+  let mut fftwf = Fftw::from_slice(single);
+  for (i, j) in fftw.compute().unwrap().iter().zip(fftwf.compute().unwrap().iter()) {
+    assert!((i.re - j.re as f64).abs() < 1e-4 && (i.im - j.im as f64).abs() < 1e-4);
+  }
+
+  let mut cmplx: FftBuf<Cmplx<f32>> = TransformBuf::new(3);
+  cmplx.push_slice([Cmplx::new(1f32, -1f32), Cmplx::new(0f32, 2f32), Cmplx::new(4f32, 1f32)]);
+  let mut out: FftBuf<Cmplx<f32>> = TransformBuf::new(3);
+  out.mark_filled();
+  let fftwc = Fftw::from_slice(cmplx.as_slice());
+  fftwc.compute_with(cmplx.as_mut_slice(), out.as_mut_slice()).unwrap();
+  assert!((out.as_slice()[0].re - 5f32).abs() < 1e-4 && (out.as_slice()[0].im - 2f32).abs() < 1e-4);
+}
+
+#[test]
+#[cfg(target_arch = "x86_64")]
+fn test_long_double() {
+  use super::{LongDouble, cmplx_to_long, long_to_cmplx};
+
+  for &x in [0f64, -2.5f64, 1e-310f64, 1f64/3f64, 1.7e308f64].iter() {
+    assert!(LongDouble::from_f64(x).to_f64() == x);
+  }
+
+  let inp = ca!{48 -2, 39 +5, 37 +3, 55 +0, 22 +210};
+  let long: ~[Cmplx<LongDouble>] = inp.iter().map(cmplx_to_long).collect();
+  let mut fftw = Fftw::from_slice(inp);
+  let mut fftwl = Fftw::from_slice(long);
+  for (i, j) in fftw.compute().unwrap().iter().zip(fftwl.compute().unwrap().iter()) {
+    assert!(approx_eq(*i, long_to_cmplx(j)));
+  }
+}
+
+#[test]
+#[cfg(fftw3q)]
+fn test_quad() {
+  use super::{Quad, cmplx_to_quad, quad_to_cmplx};
+
+  for &x in [0f64, -2.5f64, 1e-310f64, 1f64/3f64, 1.7e308f64].iter() {
+    assert!(Quad::from_f64(x).to_f64() == x);
+  }
+
+  let inp = ca!{48 -2, 39 +5, 37 +3, 55 +0, 22 +210};
+  let quad: ~[Cmplx<Quad>] = inp.iter().map(cmplx_to_quad).collect();
+  let mut fftw = Fftw::from_slice(inp);
+  let mut fftwq = Fftw::from_slice(quad);
+  for (i, j) in fftw.compute().unwrap().iter().zip(fftwq.compute().unwrap().iter()) {
+    assert!(approx_eq(*i, quad_to_cmplx(j)));
+  }
+
+  // Quad values 8 bytes away from an alignment of 16 are refused.
+  let real: Fftw<FftBuf<Quad>, FftBuf<Cmplx<Quad>>> = Fftw::new(5);
+  let mut out: ~[Cmplx<Quad>] = range(0, 3).map(|_| cmplx_to_quad(&c!(0))).collect();
+  let mut words = ::std::vec::from_elem(2 * 5 + 1, 0u64);
+  let first = if words.as_ptr() as uint % 16 == 0 { 1 } else { 0 };
+  let start = unsafe { words.as_mut_ptr().offset(first) as *mut Quad };
+  unsafe {
+    ::std::vec::raw::mut_buf_as_slice(start, 5, |values| {
+      for (v, x) in values.mut_iter().zip(inp.iter()) {
+        *v = Quad::from_f64(x.re);
+      }
+      assert!(super::check_alignment(values).err() == Some(Misaligned));
+      let res = real.compute_with(values, out.as_mut_slice());
+      assert!(res.err() == Some(Misaligned));
+    });
+  }
+}
+
+fn spectrum_2d<F: FftwFloat>(input: &[F], rows: uint, cols: uint) -> ~[Cmplx<F>] {
+  let mut fftw = Fftw::new_2d_r2c(rows, cols).unwrap();
+  fftw.ref_input().push_slice(input);
+  fftw.compute().unwrap().iter().map(|&cx| cx).collect()
+}
+
+#[test]
+fn test_generic_precision() {
+  let inp = ra!{1, 0, 2, 4, 5, 2};
+  let single: ~[f32] = inp.iter().map(|&x| x as f32).collect();
+  let double = spectrum_2d(inp, 2, 3);
+  let float = spectrum_2d(single, 2, 3);
+  assert_eq!(float.len(), 4);
+  for (i, j) in double.iter().zip(float.iter()) {
+    assert!((i.re - j.re as f64).abs() < 1e-4 && (i.im - j.im as f64).abs() < 1e-4);
+  }
+  assert!(format!("{}", Line(float.slice_to(1))).starts_with("[ 14"));
+
+  let mut fftwf = Fftw::from_slice(single);
+  fftwf.compute();
+  let all: ~[Cmplx<f32>] = fftwf.iter_symmetry().collect();
+  assert_eq!(all.len(), 6);
+  assert!(all[5].re == all[1].re && all[5].im == -all[1].im);
+}
+
+#[test]
+fn test_measure_rigor() {
+  let _g = unsafe { PLANNER_STATE_LOCK.lock() };
+  let inp = ca!{1 +0, 2 -1, 0 +3, 4 +4, 5 -2, 1 +1};
+  let mut estimate = Fftw::from_slice_nd([2, 3], inp).unwrap();
+  let mut measure = Fftw::from_slice_nd_with([2, 3], inp, Measure).unwrap();
+  for (i, j) in measure.input().iter().zip(inp.iter()) {
+    assert!(i == j);
+  }
+  let expected = estimate.compute().unwrap().to_owned();
+  for (i, j) in measure.compute().unwrap().iter().zip(expected.iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+
+  let vec = hca!{1 +0, 2 -1, 0 +3, 4 +4, 5 -2, 1 +1};
+  let mut fftw = Fftw::from_vec_with(vec, Measure).unwrap();
+  for (i, j) in fftw.input().iter().zip(inp.iter()) {
+    assert!(i == j);
+  }
+  let out: ~[Cmplx<f64>] = fftw.compute().unwrap().iter().map(|&cx| cx).collect();
+  assert!(approx_eq(out[0], Cmplx::new(13f64, 5f64)));
+}
+
+#[test]
+fn test_planner_options() {
+  let _g = unsafe { PLANNER_STATE_LOCK.lock() };
+  let planner = Planner::new(Patient).with_time_limit(0.5).with_conserved_memory();
+  assert!(planner.rigor() == Patient && planner.time_limit() == Some(0.5));
+  assert!(planner.fftw_flags() & Patient.fftw_flags() != 0);
+  assert!(Measure.to_planner() == Planner::new(Measure));
+  assert!(Planner::new(Estimate).with_unaligned() != Planner::new(Estimate));
+
+  // A one dimensional complex to real transform can preserve its input.
+  let inp = ca!{48 -2, 39 +5, 37 +3, 55 +0, 22 +210};
+  let mut expected = Fftw::from_slice_c2r(inp);
+  let preserving = Planner::new(Measure).with_preserved_input().with_time_limit(0.1);
+  let mut fftw = Fftw::from_slice_c2r_with(inp, preserving).unwrap();
+  let out = fftw.compute().unwrap().to_owned();
+  for (i, j) in fftw.input().iter().zip(inp.iter()) {
+    assert!(i == j);
+  }
+  for (i, j) in out.iter().zip(expected.compute().unwrap().iter()) {
+    assert!((*i - *j).abs() < 1e-9);
+  }
+  // A multi-dimensional one can not.
+  let preserving = Planner::new(Estimate).with_preserved_input();
+  let fftw: Result<Fftw<FftBuf<Cmplx<f64>>, FftBuf<f64>>, FftwError> =
+    Fftw::new_2d_c2r_with(4, 6, preserving);
+  assert!(fftw.err() == Some(NoPlan));
+}
+
+#[test]
+fn test_wisdom() {
+  let _g = unsafe { PLANNER_STATE_LOCK.lock() };
+  let inp = ca!{1 +0, 2 -1, 0 +3, 4 +4, 5 -2, 1 +1, 0 +0, 3 -3};
+  let mut fftw = Fftw::from_slice_nd_with([8], inp, Measure).unwrap();
+  fftw.compute();
+  let wisdom = Wisdom::double();
+  let exported = wisdom.export_to_str();
+  assert!(exported.contains("fftw_wisdom"));
+  assert!(wisdom.import_from_str(exported).is_ok());
+  assert!(wisdom.import_from_str("no wisdom here").err() == Some(InvalidWisdom));
+  // The wisdom of one precision is rejected by the others.
+  assert!(Wisdom::single().import_from_str(exported).err() == Some(InvalidWisdom));
+
+  let path = ::std::os::tmpdir().join("fftw3_rust_test.wisdom");
+  wisdom.export_to_file(&path).unwrap();
+  wisdom.forget();
+  assert!(wisdom.import_from_file(&path).is_ok());
+  assert!(wisdom.export_to_str() == exported);
+  ::std::io::fs::unlink(&path).unwrap();
+  assert!(wisdom.import_from_file(&path).err() == Some(WisdomFile(path.clone())));
+}
+
+#[test]
+fn test_init_wisdom() {
+  let _g = unsafe { PLANNER_STATE_LOCK.lock() };
+  let dir = ::std::os::tmpdir();
+  let double = dir.join("fftw3_rust_test_init.wisdom");
+  let single = dir.join("fftw3_rust_test_init.wisdomf");
+  let invalid = dir.join("fftw3_rust_test_init.invalid");
+  let missing = dir.join("fftw3_rust_test_init.missing");
+  Wisdom::double().export_to_file(&double).unwrap();
+  Wisdom::single().export_to_file(&single).unwrap();
+  ::std::io::File::create(&invalid).write_str("not wisdom").unwrap();
+
+  let report = init_wisdom([double.clone(), single.clone(), invalid.clone(),
+                            missing.clone()]);
+  assert!(report.system.len() >= 2);
+  assert!(report.files == ~[(double.clone(), Ok(Wisdom::double())),
+                            (single.clone(), Ok(Wisdom::single())),
+                            (invalid.clone(), Err(WisdomFile(invalid.clone()))),
+                            (missing.clone(), Err(WisdomFile(missing.clone())))]);
+  assert!(!report.all_imported());
+  for path in [double, single, invalid].iter() {
+    ::std::io::fs::unlink(path).unwrap();
+  }
+}
+
+#[test]
+fn test_plan_cache() {
+  let _g = unsafe { PLANNER_STATE_LOCK.lock() };
+  let dir = ::std::os::tmpdir().join("fftw3_rust_test_cache");
+  let _ = ::std::io::fs::rmdir_recursive(&dir);
+  let inp = ca!{1 +0, 2 -1, 0 +3, 4 +4, 5 -2, 1 +1, 0 +0, 3 -3};
+  let mut estimate = Fftw::from_slice_nd([2, 4], inp).unwrap();
+  let expected = estimate.compute().unwrap().to_owned();
+
+  let planner = Planner::new(Measure).with_cache(PlanCache::open(&dir).unwrap());
+  let mut first = Fftw::from_slice_nd_with([2, 4], inp, planner).unwrap();
+  assert!(dir.join("plans").exists() && dir.join("wisdom.double").exists());
+  for (i, j) in first.compute().unwrap().iter().zip(expected.iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+  // Batches are recorded with their layout, real to real transforms with their kinds.
+  let planner = Planner::new(Measure).with_cache(PlanCache::open(&dir).unwrap());
+  let _batch: Fftw<FftBuf<Cmplx<f64>>, FftBuf<Cmplx<f64>>> =
+    Fftw::new_many_with(4, 2, 2, 1, planner.clone()).unwrap();
+  let _dct: Fftw<FftBuf<f64>, FftBuf<f64>> = Fftw::new_r2r_with(8, Dct2, planner).unwrap();
+  let plans = ::std::io::File::open(&dir.join("plans")).read_to_str().unwrap();
+  assert!(plans.contains("signals") && plans.contains("kinds"));
+
+  // The recorded transform is planned from the wisdom saved in the cache.
+  Wisdom::double().forget();
+  let planner = Planner::new(Measure).with_cache(PlanCache::open(&dir).unwrap());
+  let mut second = Fftw::from_slice_nd_with([2, 4], inp, planner.clone()).unwrap();
+  for (i, j) in second.compute().unwrap().iter().zip(expected.iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+  // Without the wisdom, it is planned normally again.
+  Wisdom::double().forget();
+  let mut third = Fftw::from_slice_nd_with([2, 4], inp, planner).unwrap();
+  for (i, j) in third.compute().unwrap().iter().zip(expected.iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+
+  // The cache of another version of fftw3 is emptied.
+  ::std::io::File::create(&dir.join("version")).write_str("fftw-0.0.0").unwrap();
+  PlanCache::open(&dir).unwrap();
+  assert!(!dir.join("plans").exists() && !dir.join("wisdom.double").exists());
+  ::std::io::fs::rmdir_recursive(&dir).unwrap();
+}
+
+#[test]
+#[cfg(fftw3_threads)]
+fn test_threads() {
+  let _g = unsafe { PLANNER_STATE_LOCK.lock() };
+  assert!(init_threads());
+  let planner = Planner::new(Measure).with_threads(4);
+  assert!(planner.threads() == 4 && Estimate.to_planner().threads() == 1);
+  assert!(Planner::new(Measure).with_threads(0).threads() == 1);
+  let inp: ~[Cmplx<f64>] = range(0, 64 * 32).map(|i| {
+    Cmplx::new((i % 7) as f64, (i % 3) as f64)
+  }).collect();
+  let mut single = Fftw::from_slice_nd([64, 32], inp).unwrap();
+  let mut threaded = Fftw::from_slice_nd_with([64, 32], inp, planner).unwrap();
+  let expected = single.compute().unwrap().to_owned();
+  for (i, j) in threaded.compute().unwrap().iter().zip(expected.iter()) {
+    assert!(approx_eq(*i, *j));
+  }
+}
+
+fn bench<T: Transform<Cmplx<f64>>>(slice: &[T], domain: &str) {
   let (mut t1, mut t2) = (0u64, 0u64);
   for i in range(0u64, 1000u64) {
     let start = precise_time_ns();
@@ -152,6 +948,45 @@ fn bench<T: TransformData>(slice: &[T], domain: &str) {
                                                  t2 as f64/1000f64));
 }
 
+#[cfg(fftw3_threads)]
+fn bench_threads(rows: uint, cols: uint, threads: uint) {
+  use std::rand::{rng, Rng};
+
+  let _g = unsafe { PLANNER_STATE_LOCK.lock() };
+  let mut rng = rng();
+  let buff: ~[Cmplx<f64>] = range(0, rows * cols).map(|_| {
+    Cmplx::new(rng.gen_range(-100f64, 100f64), rng.gen_range(-100f64, 100f64))
+  }).collect();
+  let planner = Planner::new(Measure).with_threads(threads);
+  let mut single = Fftw::from_slice_nd_with([rows, cols], buff, Measure).unwrap();
+  let mut threaded = Fftw::from_slice_nd_with([rows, cols], buff, planner).unwrap();
+
+  let (mut t1, mut t2) = (0u64, 0u64);
+  for i in range(0u64, 100u64) {
+    let start = precise_time_ns();
+    single.compute();
+    let time1 = precise_time_ns();
+    threaded.compute();
+    let time2 = precise_time_ns();
+    t1 = (i*t1 + (time1-start))/(i+1);
+    t2 = (i*t2 + (time2-time1))/(i+1);
+  }
+  // The threads only split the work, the transform is the same.
+  for (i, j) in threaded.output().iter().zip(single.output().iter()) {
+    assert!((*i - *j).norm() < 1e-9 * (rows * cols) as f64);
+  }
+
+  let _ = ::std::io::stdout().write_line(format!("{}x{}, 1 thread : {}ms, {} threads : {}ms",
+                                                 rows, cols, t1 as f64/1000f64, threads,
+                                                 t2 as f64/1000f64));
+}
+
+#[test]
+#[cfg(fftw3_threads)]
+fn bench_threads_512x512() {
+  bench_threads(512, 512, 4);
+}
+
 #[test]
 fn bench_real_10000() {
   use std::rand::{rng, Rng};
@@ -0,0 +1,290 @@
+// Copyright (c) 2014 Raphael Catolino
+//! Wisdom : the knowledge accumulated by the planner about the fastest plans.
+//!
+//! Every plan made with more rigor than `Estimate` is remembered by fftw3, and planning
+//! the same transform again is then almost free. Exporting the wisdom and importing it
+//! at the next start gives plans of the same quality without the planning time. Each
+//! precision has its own wisdom, held by its own version of fftw3.
+//!
+//! ```rust
+//! use fftw3_rust::{Fftw, Wisdom, Measure};
+//!
+//! let wisdom = Wisdom::double();
+//! let path = std::os::tmpdir().join("fftw.wisdom");
+//! // Planning is fast if the wisdom of a previous run is available.
+//! let _ = wisdom.import_from_file(&path);
+//! let mut fftw = Fftw::from_slice_with([1f64, 0f64, 2f64, 4f64], Measure).unwrap();
+//! wisdom.export_to_file(&path).unwrap();
+//! ```
+//!
+//! `init_wisdom` loads at once the wisdom of the system and of several files into every
+//! precision, and is meant to be called at startup before making any transform.
+//!
+//! ```rust
+//! use fftw3_rust::init_wisdom;
+//!
+//! let report = init_wisdom([Path::new("/var/cache/app/fftw.wisdom")]);
+//! for &(ref path, ref res) in report.files.iter() {
+//!   match *res {
+//!     Ok(wisdom) => println!("{} : {} precision", path.display(), wisdom),
+//!     Err(ref err) => println!("planning without some wisdom : {}", err),
+//!   }
+//! }
+//! ```
+
+use fftw3_bindgen::{fftw_forget_wisdom, fftw_export_wisdom_to_filename,
+                    fftw_export_wisdom_to_string, fftw_import_wisdom_from_filename,
+                    fftw_import_wisdom_from_string, fftwf_forget_wisdom,
+                    fftwf_export_wisdom_to_filename, fftwf_export_wisdom_to_string,
+                    fftwf_import_wisdom_from_filename, fftwf_import_wisdom_from_string,
+                    fftw_import_system_wisdom, fftwf_import_system_wisdom, fftw_version,
+                    fftwf_version, fftw_free, fftwf_free};
+#[cfg(target_arch = "x86_64")]
+use fftw3_bindgen::{fftwl_forget_wisdom, fftwl_export_wisdom_to_filename,
+                    fftwl_export_wisdom_to_string, fftwl_import_wisdom_from_filename,
+                    fftwl_import_wisdom_from_string, fftwl_import_system_wisdom,
+                    fftwl_version, fftwl_free};
+
+use std::c_str::{CString, ToCStr};
+use std::fmt::{Show, Formatter};
+use std::fmt;
+use std::libc::{c_char, c_void};
+
+#[cfg(fftw3q)]
+use fftw3_quad;
+
+use super::{FftwError, InvalidWisdom, WisdomFile, NoSystemWisdom, Precision, PRECISIONS,
+            Double, Single, LOCK};
+#[cfg(target_arch = "x86_64")]
+use super::Extended;
+#[cfg(fftw3q)]
+use super::Quadruple;
+
+/// The wisdom of the fftw3 library of one precision.
+#[deriving(Eq, Clone)]
+pub struct Wisdom {
+  priv precision: Precision,
+}
+
+impl Wisdom {
+  /// Returns the wisdom of the double precision (f64) transforms.
+  pub fn double() -> Wisdom {
+    Wisdom { precision: Double }
+  }
+
+  /// Returns the wisdom of the single precision (f32) transforms.
+  pub fn single() -> Wisdom {
+    Wisdom { precision: Single }
+  }
+
+  /// Returns the wisdom of the extended precision (`LongDouble`) transforms.
+  #[cfg(target_arch = "x86_64")]
+  pub fn extended() -> Wisdom {
+    Wisdom { precision: Extended }
+  }
+
+  /// Returns the wisdom of the quadruple precision (`Quad`) transforms.
+  #[cfg(fftw3q)]
+  pub fn quadruple() -> Wisdom {
+    Wisdom { precision: Quadruple }
+  }
+
+  /// Writes the wisdom to the file at 'path', replacing its content.
+  /// Returns an error if the file could not be written.
+  pub fn export_to_file(&self, path: &Path) -> Result<(), FftwError> {
+    let done = path.with_c_str(|filename| unsafe {
+      let _g = LOCK.lock();
+      match self.precision {
+        Double => fftw_export_wisdom_to_filename(filename),
+        Single => fftwf_export_wisdom_to_filename(filename),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_export_wisdom_to_filename(filename),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::export_wisdom_to_filename(filename),
+      }
+    });
+    if done != 0 {
+      Ok(())
+    } else {
+      Err(WisdomFile(path.clone()))
+    }
+  }
+
+  /// Returns the wisdom as a string, in the text format of fftw3.
+  pub fn export_to_str(&self) -> ~str {
+    let raw = unsafe {
+      let _g = LOCK.lock();
+      match self.precision {
+        Double => fftw_export_wisdom_to_string(),
+        Single => fftwf_export_wisdom_to_string(),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_export_wisdom_to_string(),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::export_wisdom_to_string(),
+      }
+    };
+    // The string is copied, and then freed by the library which allocated it.
+    let exported = unsafe { CString::new(raw as *c_char, false) };
+    let wisdom = exported.as_str().unwrap_or("").to_owned();
+    unsafe {
+      match self.precision {
+        Double => fftw_free(raw as *mut c_void),
+        Single => fftwf_free(raw as *mut c_void),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_free(raw as *mut c_void),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::free_wisdom_string(raw),
+      }
+    }
+    wisdom
+  }
+
+  /// Adds the wisdom stored in the file at 'path' to the current one.
+  /// Returns an error if the file could not be read or does not hold wisdom of this
+  /// precision.
+  pub fn import_from_file(&self, path: &Path) -> Result<(), FftwError> {
+    let done = path.with_c_str(|filename| unsafe {
+      let _g = LOCK.lock();
+      match self.precision {
+        Double => fftw_import_wisdom_from_filename(filename),
+        Single => fftwf_import_wisdom_from_filename(filename),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_import_wisdom_from_filename(filename),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::import_wisdom_from_filename(filename),
+      }
+    });
+    if done != 0 {
+      Ok(())
+    } else {
+      Err(WisdomFile(path.clone()))
+    }
+  }
+
+  /// Adds the wisdom held by 'wisdom', as returned by `export_to_str`, to the current
+  /// one.
+  /// Returns an error if the string does not hold wisdom of this precision.
+  pub fn import_from_str(&self, wisdom: &str) -> Result<(), FftwError> {
+    let done = wisdom.with_c_str(|input| unsafe {
+      let _g = LOCK.lock();
+      match self.precision {
+        Double => fftw_import_wisdom_from_string(input),
+        Single => fftwf_import_wisdom_from_string(input),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_import_wisdom_from_string(input),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::import_wisdom_from_string(input),
+      }
+    });
+    if done != 0 {
+      Ok(())
+    } else {
+      Err(InvalidWisdom)
+    }
+  }
+
+  /// Adds the wisdom of the system, stored in /etc/fftw by default, to the current one.
+  /// Returns an error if the system does not hold wisdom of this precision.
+  pub fn import_system(&self) -> Result<(), FftwError> {
+    let done = unsafe {
+      let _g = LOCK.lock();
+      match self.precision {
+        Double => fftw_import_system_wisdom(),
+        Single => fftwf_import_system_wisdom(),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_import_system_wisdom(),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::import_system_wisdom(),
+      }
+    };
+    if done != 0 {
+      Ok(())
+    } else {
+      Err(NoSystemWisdom)
+    }
+  }
+
+  /// Returns the version of the fftw3 library holding this wisdom. Wisdom exported by
+  /// one version is not meant to be imported by another.
+  pub fn library_version(&self) -> ~str {
+    let raw = unsafe {
+      match self.precision {
+        Double => fftw_version.as_ptr(),
+        Single => fftwf_version.as_ptr(),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_version.as_ptr(),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::version(),
+      }
+    };
+    // The version is a static string of the library.
+    let version = unsafe { CString::new(raw, false) };
+    version.as_str().unwrap_or("").to_owned()
+  }
+
+  /// Drops all the wisdom accumulated so far. The existing plans are not affected.
+  pub fn forget(&self) {
+    unsafe {
+      let _g = LOCK.lock();
+      match self.precision {
+        Double => fftw_forget_wisdom(),
+        Single => fftwf_forget_wisdom(),
+        #[cfg(target_arch = "x86_64")]
+        Extended => fftwl_forget_wisdom(),
+        #[cfg(fftw3q)]
+        Quadruple => fftw3_quad::forget_wisdom(),
+      }
+    }
+  }
+}
+
+impl Show for Wisdom {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f.buf, "{}", self.precision)
+  }
+}
+
+/// Outcome of `init_wisdom`, for each source of wisdom.
+pub struct WisdomReport {
+  /// The result of the import of the system wisdom, for each precision.
+  system: ~[(Wisdom, Result<(), FftwError>)],
+  /// For each file, the precision its wisdom was added to, or the error if none of them
+  /// accepted it.
+  files: ~[(Path, Result<Wisdom, FftwError>)],
+}
+
+impl WisdomReport {
+  /// Returns true if the system wisdom of every precision and every file was imported.
+  pub fn all_imported(&self) -> bool {
+    self.system.iter().all(|&(_, ref res)| res.is_ok()) &&
+      self.files.iter().all(|&(_, ref res)| res.is_ok())
+  }
+}
+
+/// Returns the wisdom of 'precision'.
+pub fn wisdom_of(precision: Precision) -> Wisdom {
+  Wisdom { precision: precision }
+}
+
+/// Returns the wisdom of every precision this library was built with.
+pub fn available() -> ~[Wisdom] {
+  PRECISIONS.iter().map(|&precision| wisdom_of(precision)).collect()
+}
+
+/// Imports the system wisdom of every precision, then the wisdom of each of 'files'.
+/// A file holds the wisdom of a single precision, so each file is added to the first
+/// precision accepting it. The wisdom of several files of the same precision is merged.
+/// Returns what could be imported; nothing that failed is fatal, the transforms are only
+/// planned without that knowledge.
+pub fn init_wisdom(files: &[Path]) -> WisdomReport {
+  let precisions = available();
+  let system = precisions.iter().map(|wisdom| (*wisdom, wisdom.import_system())).collect();
+  let files = files.iter().map(|path| {
+    let res = match precisions.iter().find(|wisdom| wisdom.import_from_file(path).is_ok()) {
+      Some(wisdom) => Ok(*wisdom),
+      None => Err(WisdomFile(path.clone())),
+    };
+    (path.clone(), res)
+  }).collect();
+  WisdomReport { system: system, files: files }
+}